@@ -0,0 +1,74 @@
+use crate::utils::SynErrors;
+
+/// The options given with `#[bit_field(…)]`.
+#[derive(Default)]
+pub(super) struct BitFieldAttrs {
+    /// `bits = N`.
+    pub(super) bits: Option<syn::LitInt>,
+    /// `catch_all`, on enum variants.
+    pub(super) catch_all: Option<syn::Path>,
+    /// `compact`, on enums.
+    pub(super) compact: Option<syn::Path>,
+}
+
+pub(super) fn parse_attrs(attrs: &[syn::Attribute]) -> syn::Result<BitFieldAttrs> {
+    let mut result = BitFieldAttrs::default();
+    let mut errors = SynErrors::default();
+    for attr in attrs {
+        if !attr.path.is_ident("bit_field") {
+            continue;
+        }
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            meta => {
+                errors.push(syn::Error::new_spanned(
+                    meta,
+                    "expected `#[bit_field(…)]` with a list of options",
+                ));
+                continue;
+            }
+        };
+        for option in list.nested {
+            match option {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit,
+                    ..
+                })) if path.is_ident("bits") => {
+                    if result.bits.is_some() {
+                        errors.push(syn::Error::new_spanned(path, "duplicate `bits` option"));
+                        continue;
+                    }
+                    match lit {
+                        syn::Lit::Int(lit) => match lit.base10_parse::<u32>() {
+                            Ok(_) => result.bits = Some(lit),
+                            Err(err) => errors.push(err),
+                        },
+                        lit => errors.push(syn::Error::new_spanned(
+                            lit,
+                            "expected an integer: `#[bit_field(bits = N)]`",
+                        )),
+                    }
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("catch_all") => {
+                    if result.catch_all.is_some() {
+                        errors.push(syn::Error::new_spanned(path, "duplicate `catch_all` option"));
+                        continue;
+                    }
+                    result.catch_all = Some(path);
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("compact") => {
+                    if result.compact.is_some() {
+                        errors.push(syn::Error::new_spanned(path, "duplicate `compact` option"));
+                        continue;
+                    }
+                    result.compact = Some(path);
+                }
+                option => errors
+                    .push(syn::Error::new_spanned(option, "unknown option for `#[bit_field(…)]`")),
+            }
+        }
+    }
+    errors.into_result()?;
+    Ok(result)
+}
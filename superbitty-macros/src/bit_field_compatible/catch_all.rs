@@ -0,0 +1,191 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+
+use crate::utils::{is_unsigned_int_primitive, SynErrors};
+
+/// Implements `BitFieldCompatible` for an enum with a `#[bit_field(catch_all)]` variant.
+///
+/// The raw value of a unit variant is its discriminant, computed with the same rules
+/// as Rust's (we cannot just cast with `as` since the enum has a variant with fields).
+/// Every other value of the width of the catch-all's payload decodes to the catch-all.
+pub(super) fn catch_all(
+    item: &syn::DeriveInput,
+    enum_: &syn::DataEnum,
+    catch_all_index: usize,
+    bits: Option<&syn::LitInt>,
+) -> syn::Result<TokenStream> {
+    let catch_all = &enum_.variants[catch_all_index];
+    let payload = verify_variants(enum_, catch_all)?;
+    let payload_ty = &payload.ty;
+    let catch_all_name = &catch_all.ident;
+    let catch_all_const = format_ident!("__superbitty_catch_all");
+
+    let mut discriminant_consts = TokenStream::new();
+    let mut previous = None;
+    for variant in &enum_.variants {
+        let const_name =
+            if variant.ident == *catch_all_name { &catch_all_const } else { &variant.ident };
+        let value = match (&variant.discriminant, &previous) {
+            (Some((_, discriminant)), _) => quote!((#discriminant) as ::core::primitive::u128),
+            (None, Some(previous)) => quote!(#previous + 1),
+            (None, None) => quote!(0),
+        };
+        discriminant_consts.extend(quote! {
+            const #const_name: ::core::primitive::u128 = #value;
+        });
+        previous = Some(const_name);
+    }
+    let unit_variants = enum_
+        .variants
+        .iter()
+        .filter(|variant| variant.ident != *catch_all_name)
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+
+    let payload_info = if is_unsigned_int_primitive(payload_ty) {
+        PayloadInfo {
+            width: quote!(<#payload_ty>::BITS),
+            into_raw: quote!(__payload as ::core::primitive::u128),
+            from_raw: quote!(__v as #payload_ty),
+            is_valid_raw: quote!(__v <= <#payload_ty>::MAX as ::core::primitive::u128),
+        }
+    } else {
+        let payload = quote_spanned! {payload_ty.span()=>
+            <#payload_ty as ::superbitty::BitFieldCompatible>
+        };
+        PayloadInfo {
+            width: quote!(#payload::SHIFT + #payload::BITS_LEN),
+            into_raw: quote!(#payload::into_raw(__payload)),
+            // SAFETY: We're guaranteed by precondition that `__v` is valid, and it is
+            // not one of the unit variants, so it must be valid for the payload.
+            from_raw: quote!(unsafe { #payload::from_raw(__v) }),
+            is_valid_raw: quote!(#payload::is_valid_raw(__v)),
+        }
+    };
+    let PayloadInfo { width, into_raw, from_raw, is_valid_raw } = payload_info;
+
+    let bits_len = quote! {
+        {
+            #discriminant_consts
+            #(
+                match #unit_variants.checked_shr(#width) {
+                    ::superbitty::__helpers::Some(0) | ::superbitty::__helpers::None => {}
+                    _ => panic!("the discriminants of the unit variants must fit in the catch-all variant"),
+                }
+            )*
+            #width
+        }
+    };
+    let (shift, bits_len) = super::with_bits(bits, quote!(0), bits_len);
+
+    let member = match &payload.ident {
+        Some(name) => syn::Member::Named(name.clone()),
+        None => syn::Member::Unnamed(syn::Index::from(0)),
+    };
+    // The payload of generic enums may not have const conversions.
+    let const_conversions = item.generics.type_params().next().is_none().then(|| {
+        let into_raw = crate::const_raw::into_raw(payload_ty, quote!(__payload));
+        let from_raw = crate::const_raw::from_raw(payload_ty, quote!(__v));
+        super::const_conversions(
+            item,
+            quote! {
+                #discriminant_consts
+                match self {
+                    #( Self::#unit_variants => #unit_variants, )*
+                    Self::#catch_all_name { #member: __payload } => #into_raw,
+                }
+            },
+            quote! {
+                #discriminant_consts
+                match __v {
+                    #( #unit_variants => Self::#unit_variants, )*
+                    // SAFETY: Like in `from_raw()`.
+                    _ => Self::#catch_all_name { #member: unsafe { #from_raw } },
+                }
+            },
+        )
+    });
+    let type_name = &item.ident;
+    let (impl_generics, type_generics, where_clause) = item.generics.split_for_impl();
+    let result = quote! {
+        // SAFETY: The unit variants are checked to fit in the width of the payload,
+        // and the catch-all's raw value is the payload's.
+        unsafe impl #impl_generics ::superbitty::BitFieldCompatible
+            for #type_name #type_generics
+        #where_clause
+        {
+            const SHIFT: ::core::primitive::u32 = #shift;
+            #[allow(non_upper_case_globals)]
+            const BITS_LEN: ::core::primitive::u32 = #bits_len;
+
+            #[inline]
+            #[allow(non_upper_case_globals)]
+            fn into_raw(self) -> ::core::primitive::u128 {
+                #discriminant_consts
+                match self {
+                    #( Self::#unit_variants => #unit_variants, )*
+                    Self::#catch_all_name { #member: __payload } => #into_raw,
+                }
+            }
+            #[inline]
+            #[allow(non_upper_case_globals)]
+            unsafe fn from_raw(__v: ::core::primitive::u128) -> Self {
+                #discriminant_consts
+                match __v {
+                    #( #unit_variants => Self::#unit_variants, )*
+                    _ => Self::#catch_all_name { #member: #from_raw },
+                }
+            }
+            #[inline]
+            #[allow(non_upper_case_globals)]
+            fn is_valid_raw(__v: ::core::primitive::u128) -> ::core::primitive::bool {
+                #discriminant_consts
+                match __v {
+                    #( #unit_variants )|* => true,
+                    _ => #is_valid_raw,
+                }
+            }
+        }
+
+        #const_conversions
+    };
+    Ok(result)
+}
+
+struct PayloadInfo {
+    width: TokenStream,
+    into_raw: TokenStream,
+    from_raw: TokenStream,
+    is_valid_raw: TokenStream,
+}
+
+/// Verifies that all variants except the catch-all are unit variants, and returns
+/// the catch-all's payload.
+fn verify_variants<'a>(
+    enum_: &'a syn::DataEnum,
+    catch_all: &'a syn::Variant,
+) -> syn::Result<&'a syn::Field> {
+    let mut errors = SynErrors::default();
+    for variant in &enum_.variants {
+        if variant.ident != catch_all.ident && !matches!(variant.fields, syn::Fields::Unit) {
+            errors.push(syn::Error::new_spanned(
+                &variant.fields,
+                "cannot have payload with `BitFieldCompatible`",
+            ));
+        }
+    }
+    let mut fields = catch_all.fields.iter();
+    let payload = match (fields.next(), fields.next()) {
+        (Some(payload), None) => Some(payload),
+        _ => {
+            errors.push(syn::Error::new_spanned(
+                catch_all,
+                "the `#[bit_field(catch_all)]` variant must have exactly one field",
+            ));
+            None
+        }
+    };
+    errors.into_result()?;
+    Ok(payload.expect("we return an error when there is no payload"))
+}
@@ -0,0 +1,440 @@
+mod attrs;
+mod catch_all;
+mod newtype;
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+
+use crate::utils::SynErrors;
+
+pub(crate) fn bit_field_compatible(item: TokenStream) -> syn::Result<TokenStream> {
+    let item = syn::parse2::<syn::DeriveInput>(item)?;
+    let attrs = attrs::parse_attrs(&item.attrs)?;
+    if let Some(catch_all) = attrs.catch_all {
+        return Err(syn::Error::new_spanned(
+            catch_all,
+            "`#[bit_field(catch_all)]` can only be used on enum variants",
+        ));
+    }
+    if let (Some(compact), false) = (&attrs.compact, matches!(item.data, syn::Data::Enum(_))) {
+        return Err(syn::Error::new_spanned(
+            compact,
+            "`#[bit_field(compact)]` can only be used on enums",
+        ));
+    }
+    match &item.data {
+        syn::Data::Enum(enum_) => enum_impl(&item, enum_, attrs),
+        syn::Data::Struct(struct_) => newtype::newtype(&item, struct_, attrs),
+        syn::Data::Union(_) => Err(syn::Error::new_spanned(
+            &item,
+            "unions cannot `#[derive(BitFieldCompatible)]` and must implement it themselves",
+        )),
+    }
+}
+
+fn enum_impl(
+    item: &syn::DeriveInput,
+    enum_: &syn::DataEnum,
+    attrs: attrs::BitFieldAttrs,
+) -> syn::Result<TokenStream> {
+    if let Some(catch_all) = catch_all_variant(&enum_.variants)? {
+        if let Some(compact) = attrs.compact {
+            return Err(syn::Error::new_spanned(
+                compact,
+                "`#[bit_field(compact)]` cannot be used together with `#[bit_field(catch_all)]`",
+            ));
+        }
+        return catch_all::catch_all(item, enum_, catch_all, attrs.bits.as_ref());
+    }
+
+    verify_variants(&enum_.variants)?;
+    if attrs.compact.is_some() {
+        return Ok(compact_impl(item, enum_, attrs.bits.as_ref()));
+    }
+    let discriminants_mask = enum_.variants.iter().map(|variant| {
+        let name = &variant.ident;
+        quote!(Self::#name as ::core::primitive::u128)
+    });
+    let discriminants_mask = quote!(0 #( | #discriminants_mask )*);
+    // Unsigned enums cannot have negative discriminants, so they just use their discriminants.
+    // Signed enums with negative discriminants are stored in two's complement within the
+    // minimal width, starting at bit zero.
+    let is_signed = !is_unsigned_repr(&item.attrs);
+    let (shift, bits_len, into_raw) = if is_signed {
+        let discriminants = enum_.variants.iter().map(|variant| {
+            let name = &variant.ident;
+            quote!(Self::#name as ::core::primitive::i128)
+        });
+        let discriminants = quote!(&[ #(#discriminants),* ]);
+        (
+            quote! {
+                match ::superbitty::__helpers::negative_discriminants_bits_len(#discriminants) {
+                    ::superbitty::__helpers::Some(_) => 0,
+                    ::superbitty::__helpers::None => {
+                        ::superbitty::__helpers::discriminants_shift(#discriminants_mask)
+                    }
+                }
+            },
+            quote! {
+                match ::superbitty::__helpers::negative_discriminants_bits_len(#discriminants) {
+                    ::superbitty::__helpers::Some(bits_len) => bits_len,
+                    ::superbitty::__helpers::None => {
+                        ::superbitty::__helpers::discriminants_bits_len(#discriminants_mask)
+                    }
+                }
+            },
+            quote! {
+                self as ::core::primitive::i128 as ::core::primitive::u128
+                    & (<Self as ::superbitty::BitFieldCompatible>::BITS_MASK
+                        << <Self as ::superbitty::BitFieldCompatible>::SHIFT)
+            },
+        )
+    } else {
+        (
+            quote!(::superbitty::__helpers::discriminants_shift(#discriminants_mask)),
+            quote!(::superbitty::__helpers::discriminants_bits_len(#discriminants_mask)),
+            quote!(self as ::core::primitive::u128),
+        )
+    };
+    let (shift, bits_len) = with_bits(attrs.bits.as_ref(), shift, bits_len);
+    // Signed enums can take all 128 bits, for which the default mask overflows.
+    let bits_mask = is_signed.then(|| {
+        quote! {
+            const BITS_MASK: ::core::primitive::u128 = ::superbitty::__helpers::low_bits_mask(
+                <Self as ::superbitty::BitFieldCompatible>::BITS_LEN,
+            );
+        }
+    });
+    let raw_values = enum_.variants.iter().map(|variant| {
+        let name = &variant.ident;
+        if is_signed {
+            quote! {
+                Self::#name as ::core::primitive::i128 as ::core::primitive::u128
+                    & (<Self as ::superbitty::BitFieldCompatible>::BITS_MASK
+                        << <Self as ::superbitty::BitFieldCompatible>::SHIFT)
+            }
+        } else {
+            quote!(Self::#name as ::core::primitive::u128)
+        }
+    });
+    let variants = enum_.variants.iter().map(|variant| &variant.ident);
+    let from_raw = from_raw(&item.ident, variants.clone(), is_signed);
+    let const_conversions = const_conversions(item, into_raw, from_raw);
+    let conversions = conversions_via_const();
+    let is_valid_raw = is_valid_raw(&item.ident, variants.clone(), is_signed);
+    let try_from_raw = try_from_raw(&item.ident, variants, is_signed);
+    let type_name = &item.ident;
+    let (impl_generics, type_generics, where_clause) = item.generics.split_for_impl();
+    let result = quote! {
+        // SAFETY: `into_raw()` is just `as` (and masking for signed enums), and `SHIFT`
+        // and `BITS_LEN` are calculated from the discriminants.
+        unsafe impl #impl_generics ::superbitty::BitFieldCompatible
+            for #type_name #type_generics
+        #where_clause
+        {
+            const SHIFT: ::core::primitive::u32 = #shift;
+            const BITS_LEN: ::core::primitive::u32 = #bits_len;
+            #bits_mask
+            const NICHE: ::superbitty::__helpers::Option<::core::primitive::u128> = ::superbitty::__helpers::find_niche(
+                &[ #(#raw_values),* ],
+                <Self as ::superbitty::BitFieldCompatible>::SHIFT,
+                <Self as ::superbitty::BitFieldCompatible>::BITS_LEN,
+            );
+
+            #conversions
+            #is_valid_raw
+            #try_from_raw
+        }
+
+        #const_conversions
+    };
+    Ok(result)
+}
+
+/// Applies `#[bit_field(bits = N)]` to an enum, given the `SHIFT` and `BITS_LEN` it would
+/// have without it.
+///
+/// Since raw values are not shifted, the enum then starts at bit zero, and we check
+/// at compile time that all of its bits fit in the given width.
+fn with_bits(
+    bits: Option<&syn::LitInt>,
+    shift: TokenStream,
+    bits_len: TokenStream,
+) -> (TokenStream, TokenStream) {
+    match bits {
+        Some(bits) => (
+            quote!(0),
+            quote_spanned! {bits.span()=>
+                {
+                    // Not `assert!()`, because Clippy complains about it in user code.
+                    if (#shift) + (#bits_len) > #bits {
+                        panic!("the variants do not fit in the width given in `#[bit_field(bits = N)]`");
+                    }
+                    #bits
+                }
+            },
+        ),
+        None => (shift, bits_len),
+    }
+}
+
+/// Implements `BitFieldCompatible` for a `#[bit_field(compact)]` enum, that is stored
+/// as the index of the variant instead of its discriminant.
+fn compact_impl(
+    item: &syn::DeriveInput,
+    enum_: &syn::DataEnum,
+    bits: Option<&syn::LitInt>,
+) -> TokenStream {
+    let variants = enum_.variants.iter().map(|variant| &variant.ident).collect::<Vec<_>>();
+    let indices = (0..variants.len() as u128).collect::<Vec<_>>();
+    let bits_len = match variants.len() {
+        0 | 1 => 0,
+        len => u128::BITS - (len as u128 - 1).leading_zeros(),
+    };
+    let (shift, bits_len) = with_bits(bits, quote!(0), quote!(#bits_len));
+    let variants_count = variants.len() as u128;
+    let const_conversions = const_conversions(
+        item,
+        quote! {
+            match self {
+                #( Self::#variants => #indices, )*
+            }
+        },
+        quote! {
+            match __v {
+                #( #indices => Self::#variants, )*
+                // SAFETY: We're guaranteed by precondition that `__v` is a valid variant.
+                _ => unsafe { ::superbitty::__helpers::unreachable_unchecked() }
+            }
+        },
+    );
+    let conversions = conversions_via_const();
+    let type_name = &item.ident;
+    let (impl_generics, type_generics, where_clause) = item.generics.split_for_impl();
+    quote! {
+        // SAFETY: The indices of the variants are all less than `2^BITS_LEN`.
+        unsafe impl #impl_generics ::superbitty::BitFieldCompatible
+            for #type_name #type_generics
+        #where_clause
+        {
+            const SHIFT: ::core::primitive::u32 = #shift;
+            const BITS_LEN: ::core::primitive::u32 = #bits_len;
+            // The first index after the last variant, if it fits.
+            const NICHE: ::superbitty::__helpers::Option<::core::primitive::u128> =
+                match #variants_count.checked_shr(<Self as ::superbitty::BitFieldCompatible>::BITS_LEN) {
+                    ::superbitty::__helpers::Some(0) | ::superbitty::__helpers::None => {
+                        ::superbitty::__helpers::Some(#variants_count)
+                    }
+                    _ => ::superbitty::__helpers::None,
+                };
+
+            #conversions
+            #[inline]
+            fn is_valid_raw(__v: ::core::primitive::u128) -> ::core::primitive::bool {
+                __v < #variants_count
+            }
+        }
+
+        #const_conversions
+    }
+}
+
+/// Const versions of `into_raw()` and `from_raw()`, given their bodies (which use `self`
+/// and `__v`), so `#[bitfields(const)]` structs can convert the type without calling trait
+/// methods.
+fn const_conversions(
+    item: &syn::DeriveInput,
+    into_raw: TokenStream,
+    from_raw: TokenStream,
+) -> TokenStream {
+    let type_name = &item.ident;
+    let (impl_generics, type_generics, where_clause) = item.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #type_name #type_generics
+        #where_clause
+        {
+            #[doc(hidden)]
+            #[inline]
+            #[allow(non_upper_case_globals)]
+            pub const fn __superbitty_into_raw(self) -> ::core::primitive::u128 {
+                #into_raw
+            }
+
+            #[doc(hidden)]
+            #[inline]
+            // Not all conversions need `unsafe`.
+            #[allow(non_upper_case_globals, unused_unsafe)]
+            pub const unsafe fn __superbitty_from_raw(__v: ::core::primitive::u128) -> Self {
+                #from_raw
+            }
+        }
+    }
+}
+
+/// `into_raw()` and `from_raw()` of the trait, calling their [const versions].
+///
+/// [const versions]: const_conversions
+fn conversions_via_const() -> TokenStream {
+    quote! {
+        #[inline]
+        fn into_raw(self) -> ::core::primitive::u128 {
+            Self::__superbitty_into_raw(self)
+        }
+        #[inline]
+        unsafe fn from_raw(__v: ::core::primitive::u128) -> Self {
+            // SAFETY: Guaranteed by the caller.
+            unsafe { Self::__superbitty_from_raw(__v) }
+        }
+    }
+}
+
+/// The body of `from_raw()`.
+pub(crate) fn from_raw<'a>(
+    enum_name: &syn::Ident,
+    variants: impl Iterator<Item = &'a syn::Ident> + Clone,
+    is_signed: bool,
+) -> TokenStream {
+    let variant_consts = variant_consts(enum_name, variants.clone(), is_signed);
+    let variants_match_arms = variants.map(|name| {
+        quote! {
+            #name => Self::#name,
+        }
+    });
+    quote! {
+        #variant_consts
+        match __v {
+            #(#variants_match_arms)*
+            // SAFETY: We're guaranteed by precondition that `__v` is a valid variant.
+            _ => unsafe { ::superbitty::__helpers::unreachable_unchecked() }
+        }
+    }
+}
+
+pub(crate) fn is_valid_raw<'a>(
+    enum_name: &syn::Ident,
+    variants: impl Iterator<Item = &'a syn::Ident> + Clone,
+    is_signed: bool,
+) -> TokenStream {
+    let variant_consts = variant_consts(enum_name, variants.clone(), is_signed);
+    quote! {
+        #[inline]
+        #[allow(non_upper_case_globals)]
+        fn is_valid_raw(__v: ::core::primitive::u128) -> ::core::primitive::bool {
+            #variant_consts
+            match __v {
+                #( #variants )|* => true,
+                _ => false,
+            }
+        }
+    }
+}
+
+pub(crate) fn try_from_raw<'a>(
+    enum_name: &syn::Ident,
+    variants: impl Iterator<Item = &'a syn::Ident> + Clone,
+    is_signed: bool,
+) -> TokenStream {
+    let variant_consts = variant_consts(enum_name, variants.clone(), is_signed);
+    let variants_match_arms = variants.map(|name| {
+        quote! {
+            #name => ::superbitty::__helpers::Some(Self::#name),
+        }
+    });
+    quote! {
+        #[inline]
+        #[allow(non_upper_case_globals)]
+        fn try_from_raw(__v: ::core::primitive::u128) -> ::superbitty::__helpers::Option<Self> {
+            #variant_consts
+            match __v {
+                #(#variants_match_arms)*
+                _ => ::superbitty::__helpers::None,
+            }
+        }
+    }
+}
+
+/// Defines a constant with the raw value of each variant.
+fn variant_consts<'a>(
+    enum_name: &syn::Ident,
+    variants: impl Iterator<Item = &'a syn::Ident>,
+    is_signed: bool,
+) -> TokenStream {
+    variants
+        .map(|name| {
+            if is_signed {
+                quote! {
+                    const #name: ::core::primitive::u128 = #enum_name::#name as ::core::primitive::i128 as ::core::primitive::u128
+                        & (<#enum_name as ::superbitty::BitFieldCompatible>::BITS_MASK
+                            << <#enum_name as ::superbitty::BitFieldCompatible>::SHIFT);
+                }
+            } else {
+                quote! {
+                    // const blocks, please.
+                    const #name: ::core::primitive::u128 = #enum_name::#name as ::core::primitive::u128;
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the index of the `#[bit_field(catch_all)]` variant, if there is one.
+fn catch_all_variant(
+    variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
+) -> syn::Result<Option<usize>> {
+    let mut catch_all = None;
+    let mut errors = SynErrors::default();
+    for (index, variant) in variants.iter().enumerate() {
+        let attrs = attrs::parse_attrs(&variant.attrs)?;
+        if let Some(bits) = attrs.bits {
+            errors.push(syn::Error::new_spanned(
+                bits,
+                "`#[bit_field(bits = N)]` cannot be used on enum variants",
+            ));
+        }
+        if let Some(compact) = attrs.compact {
+            errors.push(syn::Error::new_spanned(
+                compact,
+                "`#[bit_field(compact)]` must be put on the enum, not on a variant",
+            ));
+        }
+        if let Some(path) = attrs.catch_all {
+            if catch_all.is_some() {
+                errors.push(syn::Error::new_spanned(
+                    path,
+                    "only one variant can be `#[bit_field(catch_all)]`",
+                ));
+            }
+            catch_all = Some(index);
+        }
+    }
+    errors.into_result()?;
+    Ok(catch_all)
+}
+
+fn verify_variants(
+    variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
+) -> syn::Result<()> {
+    let mut errors = SynErrors::default();
+    for variant in variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            errors.push(syn::Error::new_spanned(
+                &variant.fields,
+                "cannot have payload with `BitFieldCompatible`",
+            ));
+        }
+    }
+    errors.into_result()
+}
+
+/// Returns `true` if the enum has `#[repr(uN)]`.
+fn is_unsigned_repr(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().filter(|attr| attr.path.is_ident("repr")).any(|attr| {
+        let Ok(syn::Meta::List(list)) = attr.parse_meta() else { return false };
+        list.nested.iter().any(|repr| match repr {
+            syn::NestedMeta::Meta(syn::Meta::Path(repr)) => repr.get_ident().is_some_and(|repr| {
+                ["u8", "u16", "u32", "u64", "u128", "usize"].contains(&&*repr.to_string())
+            }),
+            _ => false,
+        })
+    })
+}
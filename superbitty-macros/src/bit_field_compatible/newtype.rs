@@ -0,0 +1,152 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+use super::attrs::BitFieldAttrs;
+use crate::utils::is_unsigned_int_primitive;
+
+pub(super) fn newtype(
+    item: &syn::DeriveInput,
+    struct_: &syn::DataStruct,
+    attrs: BitFieldAttrs,
+) -> syn::Result<TokenStream> {
+    let mut fields = struct_.fields.iter();
+    let field = match (fields.next(), fields.next()) {
+        (Some(field), None) => field,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                item,
+                "only structs with exactly one field can `#[derive(BitFieldCompatible)]`",
+            ))
+        }
+    };
+    let member = match &field.ident {
+        Some(name) => syn::Member::Named(name.clone()),
+        None => syn::Member::Unnamed(syn::Index::from(0)),
+    };
+    let inner_ty = &field.ty;
+
+    let type_name = &item.ident;
+    let (impl_generics, type_generics, where_clause) = item.generics.split_for_impl();
+    let result = if is_unsigned_int_primitive(inner_ty) {
+        let bits = attrs.bits.ok_or_else(|| {
+            syn::Error::new_spanned(
+                item,
+                "newtypes over primitive integers must specify their width with `#[bit_field(bits = N)]`",
+            )
+        })?;
+        let bits_len = quote_spanned! {bits.span()=>
+            {
+                // Not `assert!()`, because Clippy complains about it in user code.
+                if #bits > #inner_ty::BITS {
+                    panic!("the width given in `#[bit_field(bits = N)]` is bigger than the inner type");
+                }
+                #bits
+            }
+        };
+        let const_conversions = super::const_conversions(
+            item,
+            quote!(self.#member as ::core::primitive::u128 & <Self as ::superbitty::BitFieldCompatible>::BITS_MASK),
+            quote!(Self { #member: __v as #inner_ty }),
+        );
+        let conversions = super::conversions_via_const();
+        let field_vis = &field.vis;
+        let doc = format!(
+            "Creates a new `{type_name}`, or returns `None` if `value` does not fit in {bits} bits."
+        );
+        quote! {
+            // SAFETY: `into_raw()` masks the value to `BITS_LEN` bits, and all values
+            // that fit in these bits are valid.
+            unsafe impl #impl_generics ::superbitty::BitFieldCompatible
+                for #type_name #type_generics
+            #where_clause
+            {
+                const SHIFT: ::core::primitive::u32 = 0;
+                const BITS_LEN: ::core::primitive::u32 = #bits_len;
+
+                #conversions
+                #[inline]
+                fn is_valid_raw(v: ::core::primitive::u128) -> ::core::primitive::bool {
+                    v <= <Self as ::superbitty::BitFieldCompatible>::BITS_MASK
+                }
+            }
+
+            impl #impl_generics #type_name #type_generics
+            #where_clause
+            {
+                #[doc = #doc]
+                #[inline]
+                #[allow(dead_code)]
+                #field_vis fn new(value: #inner_ty) -> ::superbitty::__helpers::Option<Self> {
+                    if value as ::core::primitive::u128 <= <Self as ::superbitty::BitFieldCompatible>::BITS_MASK {
+                        ::superbitty::__helpers::Some(Self { #member: value })
+                    } else {
+                        ::superbitty::__helpers::None
+                    }
+                }
+            }
+
+            #const_conversions
+        }
+    } else {
+        let inner = quote_spanned! {inner_ty.span()=>
+            <#inner_ty as ::superbitty::BitFieldCompatible>
+        };
+        let (shift, bits_len) = match attrs.bits {
+            Some(bits) => (
+                quote!(0),
+                quote_spanned! {bits.span()=>
+                    {
+                        if #inner::SHIFT + #inner::BITS_LEN > #bits {
+                            panic!("the width given in `#[bit_field(bits = N)]` is smaller than the inner type");
+                        }
+                        #bits
+                    }
+                },
+            ),
+            None => (quote!(#inner::SHIFT), quote!(#inner::BITS_LEN)),
+        };
+        // The inner type of generic newtypes may not have const conversions.
+        let const_conversions = item.generics.type_params().next().is_none().then(|| {
+            let into_raw = crate::const_raw::into_raw(inner_ty, quote!(self.#member));
+            let from_raw = crate::const_raw::from_raw(inner_ty, quote!(__v));
+            super::const_conversions(
+                item,
+                into_raw,
+                quote! {
+                    // SAFETY: Our valid values are exactly those of the inner type.
+                    Self { #member: unsafe { #from_raw } }
+                },
+            )
+        });
+        quote! {
+            // SAFETY: We delegate to the inner type, and if we have a custom width we've
+            // asserted the inner type fits within it.
+            unsafe impl #impl_generics ::superbitty::BitFieldCompatible
+                for #type_name #type_generics
+            #where_clause
+            {
+                const SHIFT: ::core::primitive::u32 = #shift;
+                const BITS_LEN: ::core::primitive::u32 = #bits_len;
+                const NICHE: ::superbitty::__helpers::Option<::core::primitive::u128> = #inner::NICHE;
+
+                #[inline]
+                fn into_raw(self) -> ::core::primitive::u128 {
+                    #inner::into_raw(self.#member)
+                }
+                #[inline]
+                unsafe fn from_raw(v: ::core::primitive::u128) -> Self {
+                    // SAFETY: Our valid values are exactly those of the inner type.
+                    Self { #member: unsafe { #inner::from_raw(v) } }
+                }
+                #[inline]
+                fn is_valid_raw(v: ::core::primitive::u128) -> ::core::primitive::bool {
+                    #inner::is_valid_raw(v)
+                }
+            }
+
+            #const_conversions
+        }
+    };
+    Ok(result)
+}
@@ -30,13 +30,13 @@ mod derive_fns {
 
     pub(super) fn debug(span: Span, item: &parse::BitfieldsStruct) -> TokenStream {
         let type_name_as_string = item.ident.to_string();
-        let fields =
-            item.fields.fields.iter().map(|parse::BitfieldsStructField { ident: name, .. }| {
-                let name_as_str = name.to_string();
-                quote_spanned! {span=>
-                    s.field(#name_as_str, &self.#name());
-                }
-            });
+        let fields = item.fields.fields().map(|field| {
+            let name_as_str = field.ident.to_string();
+            let getter_name = field.getter_name();
+            quote_spanned! {span=>
+                s.field(#name_as_str, &self.#getter_name());
+            }
+        });
         impl_trait! {span=>
             impl Debug for #item {
                 fn fmt(
@@ -74,7 +74,7 @@ mod derive_fns {
     }
 
     pub(super) fn default(span: Span, item: &parse::BitfieldsStruct) -> TokenStream {
-        let field_types = item.fields.fields.iter().map(|field| &field.ty);
+        let field_types = item.fields.fields().map(|field| &field.ty);
         let constructor_name = super::super::constructor_name(&item.constructor);
         impl_trait! {span=>
             impl Default for #item {
@@ -91,16 +91,22 @@ mod derive_fns {
     }
 
     pub(super) fn partial_eq(span: Span, item: &parse::BitfieldsStruct) -> TokenStream {
-        let field_names = item.fields.fields.iter().map(|field| &field.ident);
+        let mut fields_eq = item.fields.fields().map(|field| {
+            let field_name = field.getter_name();
+            quote_spanned! {span=>
+                ::superbitty::__helpers::PartialEq::eq(&self.#field_name(), &_other.#field_name())
+            }
+        });
+        // We don't start with `true &&` because Clippy complains about it in user code.
+        let eq = match fields_eq.next() {
+            Some(first) => quote_spanned!(span=> #first #( && #fields_eq )*),
+            None => quote_spanned!(span=> true),
+        };
         impl_trait! {span=>
             impl PartialEq for #item {
                 #[inline]
-                fn eq(&self, _other: &Self) -> bool {
-                    true #(
-                        && ::superbitty::__helpers::PartialEq::eq(
-                            &self.#field_names(), &_other.#field_names(),
-                        )
-                    )*
+                fn eq(&self, _other: &Self) -> ::core::primitive::bool {
+                    #eq
                 }
             }
         }
@@ -113,7 +119,7 @@ mod derive_fns {
     }
 
     pub(super) fn partial_ord(span: Span, item: &parse::BitfieldsStruct) -> TokenStream {
-        let field_names = item.fields.fields.iter().map(|field| &field.ident);
+        let field_names = item.fields.fields().map(|field| field.getter_name());
         impl_trait! {span=>
             impl PartialOrd for #item {
                 #[inline]
@@ -133,7 +139,7 @@ mod derive_fns {
     }
 
     pub(super) fn ord(span: Span, item: &parse::BitfieldsStruct) -> TokenStream {
-        let field_names = item.fields.fields.iter().map(|field| &field.ident);
+        let field_names = item.fields.fields().map(|field| field.getter_name());
         impl_trait! {span=>
             impl Ord for #item {
                 #[inline]
@@ -151,7 +157,7 @@ mod derive_fns {
     }
 
     pub(super) fn hash(span: Span, item: &parse::BitfieldsStruct) -> TokenStream {
-        let field_names = item.fields.fields.iter().map(|field| &field.ident);
+        let field_names = item.fields.fields().map(|field| field.getter_name());
         impl_trait! {span=>
             impl Hash for #item {
                 #[inline]
@@ -23,10 +23,18 @@ pub(crate) fn bitfields_impl(item: TokenStream) -> syn::Result<TokenStream> {
 
     let base_ty = &item.base_ty;
 
-    let (bitfields, after_last_bitfield_offset) = bitfields(item.fields);
+    let Layout {
+        bitfields,
+        alternatives,
+        views,
+        kept_reserved_bits,
+        after_last_bitfield_offset,
+        checks: layout_checks,
+        bits_len,
+    } = layout(item.fields, base_ty, item.order);
     let assert_bitfields_size = quote! {
         const _: () = assert!(
-            #after_last_bitfield_offset <= #base_ty::BITS,
+            #after_last_bitfield_offset <= ::superbitty::Raw::<#base_ty>::BITS,
             "bitfield size is too big - choose another base type",
         );
     };
@@ -36,15 +44,51 @@ pub(crate) fn bitfields_impl(item: TokenStream) -> syn::Result<TokenStream> {
         constructor_name(&item.constructor),
         constructor_vis(&item.constructor, &item.vis),
         &item.base_ty,
+        item.const_token,
     );
+    let from_raw_methods = from_raw_methods(
+        &bitfields,
+        &kept_reserved_bits,
+        constructor_vis(&item.constructor, &item.vis),
+        &item.base_ty,
+    );
+    let bytes_methods =
+        bytes_methods(&item.vis, constructor_vis(&item.constructor, &item.vis), &item.base_ty);
     let bitfields_accessors =
-        bitfields.iter().map(|bitfield| bitfield_accessors(bitfield, &item.base_ty));
+        bitfields.iter().map(|bitfield| bitfield_accessors(bitfield, item.const_token));
+    let alternatives_accessors = alternatives.iter().map(alternative_accessors);
+    let views_methods = views.iter().map(|view| view_method(view, &item.vis));
+
+    let bit_field_compatible_impl = bit_field_compatible_impl(
+        &item.ident,
+        &item.generics,
+        &item.base_ty,
+        &bitfields,
+        &bits_len,
+    );
 
     let struct_vis = &item.vis;
     let struct_kw = &item.struct_token;
     let struct_name = &item.ident;
     let generics = &item.generics;
     let (impl_generics, type_generics, where_clause) = item.generics.split_for_impl();
+    // Trait impls are as visible as the type, so they must not expose a restricted
+    // constructor.
+    let try_from_impl = match constructor_vis(&item.constructor, &item.vis) {
+        syn::Visibility::Public(_) => quote! {
+            impl #impl_generics ::superbitty::__helpers::TryFrom<#base_ty> for #struct_name #type_generics
+            #where_clause
+            {
+                type Error = ::superbitty::InvalidBits;
+
+                #[inline]
+                fn try_from(raw: #base_ty) -> ::superbitty::__helpers::Result<Self, Self::Error> {
+                    Self::from_raw(raw)
+                }
+            }
+        },
+        _ => TokenStream::new(),
+    };
     let result = quote! {
         #(#struct_attrs)*
         #struct_vis #struct_kw #struct_name #generics(
@@ -55,6 +99,7 @@ pub(crate) fn bitfields_impl(item: TokenStream) -> syn::Result<TokenStream> {
 
         #assert_bitfields_compatible
         #assert_bitfields_size
+        #layout_checks
 
         #derives
 
@@ -62,9 +107,17 @@ pub(crate) fn bitfields_impl(item: TokenStream) -> syn::Result<TokenStream> {
         #where_clause
         {
             #new_method
+            #from_raw_methods
+            #bytes_methods
 
             #(#bitfields_accessors)*
+            #(#alternatives_accessors)*
+            #(#views_methods)*
         }
+
+        #try_from_impl
+
+        #bit_field_compatible_impl
     };
     Ok(result)
 }
@@ -78,7 +131,7 @@ fn disallow_invalid_attributes(attrs: &[syn::Attribute]) -> syn::Result<()> {
         if !attr.path.is_ident("doc") && !attr.path.is_ident("derive") {
             errors.push(syn::Error::new_spanned(
                 attr,
-                "only doc comments and `#[derive(…)]` are allowed with `bitfields!`",
+                "only doc comments, `#[derive(…)]` and `#[bitfields(…)]` are allowed with `bitfields!`",
             ));
         }
     }
@@ -89,28 +142,358 @@ struct Bitfield {
     parse: parse::BitfieldsStructField,
     bit_offset: TokenStream,
     type_shift: TokenStream,
-    bits_mask: TokenStream,
-    is_last: bool,
-}
-
-fn bitfields(fields: parse::BitfieldsStructFields) -> (Vec<Bitfield>, TokenStream) {
-    let mut prev_offset = quote!(0);
-    let mut result = Vec::with_capacity(fields.fields.len());
-    let fields_count = fields.fields.len();
-    for (index, field) in fields.fields.into_iter().enumerate() {
-        let bit_offset = quote! { ( #prev_offset ) };
-        let field_ty = &field.ty;
-        let bits_mask = quote! {
-            <#field_ty as ::superbitty::BitFieldCompatible>::BITS_MASK
+    bits_len: TokenStream,
+}
+
+/// A field of a view that is not the primary view of its union.
+struct Alternative {
+    bitfield: Bitfield,
+    /// Whether `__raw` is valid for the primary view of the union.
+    primary_valid: TokenStream,
+}
+
+struct ViewInfo {
+    ident: Ident,
+    /// Whether `__raw` is valid for this view.
+    valid: TokenStream,
+}
+
+struct Layout {
+    bitfields: Vec<Bitfield>,
+    alternatives: Vec<Alternative>,
+    views: Vec<ViewInfo>,
+    /// The `(offset, len)` of the reserved bits that may hold any value.
+    kept_reserved_bits: Vec<(TokenStream, TokenStream)>,
+    /// The number of bits used, counting in the order of the layout.
+    after_last_bitfield_offset: TokenStream,
+    /// The `BITS_LEN` of the struct as a bitfield.
+    bits_len: TokenStream,
+    /// Compile-time checks of explicit positions.
+    checks: TokenStream,
+}
+
+fn entry_bits_len(entry: &parse::BitfieldsStructEntry) -> TokenStream {
+    match entry {
+        parse::BitfieldsStructEntry::Field(field) => {
+            let field_ty = &field.ty;
+            quote! { <#field_ty as ::superbitty::BitFieldCompatible>::BITS_LEN }
+        }
+        parse::BitfieldsStructEntry::Reserved(reserved) => {
+            let width = &reserved.width;
+            quote! { ( (#width) as ::core::primitive::u32 ) }
+        }
+        // A union is as wide as its widest view.
+        parse::BitfieldsStructEntry::Union(union) => {
+            let widths = union.views.iter().map(|view| {
+                let entries = view.entries.iter().map(entry_bits_len);
+                quote! { ( 0 #( + #entries )* ) }
+            });
+            quote! {
+                {
+                    let mut width = 0;
+                    #(
+                        if #widths > width {
+                            width = #widths;
+                        }
+                    )*
+                    width
+                }
+            }
+        }
+    }
+}
+
+/// Lays out the fields, reserved bits and unions.
+///
+/// Entries without an explicit position are placed right after the previous entry.
+/// Positions are counted from the least significant bit, or from the most significant
+/// bit with `msb0`. The views of a union all start at the start of the union.
+fn layout(
+    fields: parse::BitfieldsStructFields,
+    base_ty: &syn::Type,
+    order: parse::BitOrder,
+) -> Layout {
+    let has_positions = fields.entries.iter().any(|entry| entry.position().is_some());
+    let bit_offset = |start: &TokenStream, end: &TokenStream| match order {
+        parse::BitOrder::Lsb0 => start.clone(),
+        parse::BitOrder::Msb0 => quote! { ( ::superbitty::Raw::<#base_ty>::BITS - #end ) },
+    };
+    let mut prev_end = quote!(0);
+    // The bits each entry occupies, as `(description, span, start, end)`.
+    let mut ranges = Vec::with_capacity(fields.entries.len());
+    let mut checks = TokenStream::new();
+    let mut bitfields = Vec::with_capacity(fields.entries.len());
+    let mut alternatives = Vec::new();
+    let mut views = Vec::new();
+    let mut kept_reserved_bits = Vec::new();
+    for entry in fields.entries {
+        let bits_len = entry_bits_len(&entry);
+        let (description, span) = match &entry {
+            parse::BitfieldsStructEntry::Field(field) => {
+                (format!("`{}`", field.ident), field.ident.span())
+            }
+            parse::BitfieldsStructEntry::Reserved(reserved) => {
+                ("reserved bits".to_owned(), reserved.underscore.span)
+            }
+            parse::BitfieldsStructEntry::Union(union) => {
+                ("the union".to_owned(), union.union_token.span)
+            }
         };
-        prev_offset.extend(std::iter::once(quote! {
-            + <#field_ty as ::superbitty::BitFieldCompatible>::BITS_LEN
-        }));
-        let type_shift = quote! { <#field_ty as ::superbitty::BitFieldCompatible>::SHIFT };
-        let is_last = index == fields_count - 1;
-        result.push(Bitfield { parse: field, bit_offset, type_shift, bits_mask, is_last })
+        let (start, end) = match entry.position() {
+            Some(parse::Position { start, end }) => {
+                let (start, end) = (quote! { ( #start ) }, quote! { ( #end ) });
+                let empty_message = format!("the bit range of {description} is empty");
+                let too_big_message = format!("{description} does not fit in its bit range");
+                let too_small_message =
+                    format!("{description} leaves unused bits in its bit range");
+                checks.extend(quote_spanned! {span=>
+                    if #start >= #end {
+                        panic!(#empty_message);
+                    }
+                    if #bits_len > #end - #start {
+                        panic!(#too_big_message);
+                    }
+                    if #bits_len < #end - #start {
+                        panic!(#too_small_message);
+                    }
+                });
+                (start, end)
+            }
+            None => (quote! { ( #prev_end ) }, quote! { ( #prev_end + #bits_len ) }),
+        };
+        let entry_offset = bit_offset(&start, &end);
+        prev_end = end.clone();
+        ranges.push((description, span, start.clone(), end));
+
+        match entry {
+            parse::BitfieldsStructEntry::Field(field) => {
+                let field_ty = &field.ty;
+                let type_shift = quote! { <#field_ty as ::superbitty::BitFieldCompatible>::SHIFT };
+                bitfields.push(Bitfield {
+                    parse: field,
+                    bit_offset: entry_offset,
+                    type_shift,
+                    bits_len,
+                })
+            }
+            parse::BitfieldsStructEntry::Reserved(reserved) if reserved.keep => {
+                kept_reserved_bits.push((entry_offset, bits_len));
+            }
+            parse::BitfieldsStructEntry::Reserved(_) => {}
+            parse::BitfieldsStructEntry::Union(union) => {
+                // The bits of the union that the primary view doesn't use may be used by
+                // other views, so they may hold any value.
+                kept_reserved_bits.push((entry_offset, bits_len));
+                let mut union_views = Vec::with_capacity(union.views.len());
+                for view in union.views {
+                    let mut view_prev_end = start.clone();
+                    let mut view_fields = Vec::new();
+                    for view_entry in view.entries {
+                        let bits_len = entry_bits_len(&view_entry);
+                        let field_start = view_prev_end;
+                        view_prev_end = quote! { ( #field_start + #bits_len ) };
+                        if let parse::BitfieldsStructEntry::Field(field) = view_entry {
+                            let field_ty = &field.ty;
+                            view_fields.push(Bitfield {
+                                bit_offset: bit_offset(&field_start, &view_prev_end),
+                                type_shift: quote! {
+                                    <#field_ty as ::superbitty::BitFieldCompatible>::SHIFT
+                                },
+                                bits_len,
+                                parse: field,
+                            });
+                        }
+                    }
+                    let valid = view_fields.iter().map(
+                        |Bitfield {
+                             parse: parse::BitfieldsStructField { ty, .. },
+                             bit_offset,
+                             type_shift,
+                             bits_len,
+                         }| {
+                            quote! {
+                                && <#ty as ::superbitty::BitFieldCompatible>::is_valid_raw(
+                                    __raw.get_bits(#bit_offset, #bits_len) << #type_shift,
+                                )
+                            }
+                        },
+                    );
+                    let valid = quote! { ( true #( #valid )* ) };
+                    views.push(ViewInfo { ident: view.ident, valid: valid.clone() });
+                    union_views.push((view_fields, valid));
+                }
+                let primary_valid = union_views[union.primary].1.clone();
+                for (index, (view_fields, _)) in union_views.into_iter().enumerate() {
+                    if index == union.primary {
+                        bitfields.extend(view_fields);
+                    } else {
+                        alternatives.extend(view_fields.into_iter().map(|bitfield| Alternative {
+                            bitfield,
+                            primary_valid: primary_valid.clone(),
+                        }));
+                    }
+                }
+            }
+        }
+    }
+
+    // With `msb0`, the fields are at the top of the base type, so it is used entirely.
+    let bits_len = |after_last_bitfield_offset: &TokenStream| match order {
+        parse::BitOrder::Lsb0 => after_last_bitfield_offset.clone(),
+        parse::BitOrder::Msb0 => quote! { ::superbitty::Raw::<#base_ty>::BITS },
+    };
+    if !has_positions {
+        let after_last_bitfield_offset = quote! { ( #prev_end ) };
+        return Layout {
+            bitfields,
+            alternatives,
+            views,
+            kept_reserved_bits,
+            bits_len: bits_len(&after_last_bitfield_offset),
+            after_last_bitfield_offset,
+            checks,
+        };
+    }
+
+    for (index, (description, _, start, end)) in ranges.iter().enumerate() {
+        for (other_description, other_span, other_start, other_end) in &ranges[index + 1..] {
+            let message = format!("{description} and {other_description} overlap");
+            checks.extend(quote_spanned! {*other_span=>
+                if #start < #other_end && #other_start < #end {
+                    panic!(#message);
+                }
+            });
+        }
+    }
+    let ends = ranges.iter().map(|(_, _, _, end)| end);
+    let after_last_bitfield_offset = quote! {
+        {
+            let mut end = 0;
+            #(
+                if #ends > end {
+                    end = #ends;
+                }
+            )*
+            end
+        }
+    };
+    let checks = quote! {
+        const _: () = {
+            #checks
+        };
+    };
+    Layout {
+        bitfields,
+        alternatives,
+        views,
+        kept_reserved_bits,
+        bits_len: bits_len(&after_last_bitfield_offset),
+        after_last_bitfield_offset,
+        checks,
+    }
+}
+
+/// Allows nesting bitfields structs in other bitfields structs.
+fn bit_field_compatible_impl(
+    struct_name: &syn::Ident,
+    generics: &syn::Generics,
+    base_ty: &syn::Type,
+    bitfields: &[Bitfield],
+    bits_len: &TokenStream,
+) -> TokenStream {
+    // A niche of any field, with all other fields zeroed, is never a valid value.
+    let niche = bitfields.iter().rev().fold(
+        quote!(::superbitty::__helpers::None),
+        |rest,
+         Bitfield {
+             parse: parse::BitfieldsStructField { ty, .. }, bit_offset, type_shift, ..
+         }| {
+            quote! {
+                match <#ty as ::superbitty::BitFieldCompatible>::NICHE {
+                    ::superbitty::__helpers::Some(niche) => {
+                        ::superbitty::__helpers::Some((niche >> #type_shift) << #bit_offset)
+                    }
+                    ::superbitty::__helpers::None => #rest,
+                }
+            }
+        },
+    );
+    let mut generics = generics.clone();
+    // `BitFieldCompatible` requires `Copy`, but we don't know if the struct is `Copy`
+    // (it may be implemented manually). The higher-ranked bound defers the check
+    // to the usage, instead of erroring on the definition.
+    generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote!(for<'__superbitty> Self: ::superbitty::__helpers::Copy));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        // SAFETY: The raw value always holds valid fields, and all bits outside of
+        // them and of the reserved bits are zero, so it fits in `BITS_LEN`.
+        unsafe impl #impl_generics ::superbitty::BitFieldCompatible for #struct_name #type_generics
+        #where_clause
+        {
+            const SHIFT: ::core::primitive::u32 = 0;
+            // The methods don't use `Self::BITS_LEN`, so that wider structs still compile
+            // as long as they are not used as bitfields.
+            const BITS_LEN: ::core::primitive::u32 = {
+                let bits_len = #bits_len;
+                if bits_len > 128 {
+                    panic!("bitfields wider than 128 bits cannot be used as a bitfield");
+                }
+                bits_len
+            };
+            const NICHE: ::superbitty::__helpers::Option<::core::primitive::u128> = #niche;
+
+            #[inline]
+            fn into_raw(self) -> ::core::primitive::u128 {
+                Self::__superbitty_into_raw(self)
+            }
+
+            #[inline]
+            unsafe fn from_raw(v: ::core::primitive::u128) -> Self {
+                // SAFETY: Guaranteed by the caller.
+                unsafe { Self::__superbitty_from_raw(v) }
+            }
+
+            #[inline]
+            fn is_valid_raw(v: ::core::primitive::u128) -> ::core::primitive::bool {
+                match v.checked_shr(#bits_len) {
+                    ::superbitty::__helpers::Some(0) | ::superbitty::__helpers::None => {}
+                    _ => return false,
+                }
+                // SAFETY: This is only used to build the raw value to check.
+                let raw = unsafe {
+                    let mut raw = ::superbitty::Raw::<#base_ty>::zeroed();
+                    raw.set_bits(0, #bits_len, v);
+                    raw
+                };
+                Self::from_raw(::superbitty::Raw::raw(raw)).is_ok()
+            }
+        }
+
+        // Const versions of the conversions, for `#[bitfields(const)]` structs containing this.
+        impl #impl_generics #struct_name #type_generics
+        #where_clause
+        {
+            #[doc(hidden)]
+            #[inline]
+            pub const fn __superbitty_into_raw(self) -> ::core::primitive::u128 {
+                self.0.get_bits(0, #bits_len)
+            }
+
+            #[doc(hidden)]
+            #[inline]
+            pub const unsafe fn __superbitty_from_raw(v: ::core::primitive::u128) -> Self {
+                // SAFETY: Guaranteed by the caller.
+                unsafe {
+                    let mut raw = ::superbitty::Raw::<#base_ty>::zeroed();
+                    raw.set_bits(0, #bits_len, v);
+                    Self(raw)
+                }
+            }
+        }
     }
-    (result, quote! { ( #prev_offset) })
 }
 
 fn constructor_name(constructor: &parse::Constructor) -> Ident {
@@ -134,6 +517,7 @@ fn new_method(
     method_name: syn::Ident,
     method_vis: &syn::Visibility,
     base_ty: &syn::Type,
+    const_token: Option<syn::Token![const]>,
 ) -> TokenStream {
     let args = bitfields.iter().map(
         |Bitfield { parse: parse::BitfieldsStructField { ident: name, ty, .. }, .. }| quote!(#name : #ty),
@@ -143,66 +527,356 @@ fn new_method(
              parse: parse::BitfieldsStructField { ident: name, ty, .. },
              bit_offset,
              type_shift,
-             ..
+             bits_len,
          }| {
+            let raw_field = field_into_raw(ty, quote!(#name), const_token);
             quote! {
-                ((<#ty as ::superbitty::BitFieldCompatible>::into_raw(#name) >> #type_shift)
-                    << #bit_offset)
+                raw.set_bits(#bit_offset, #bits_len, #raw_field >> #type_shift);
             }
         },
     );
     quote! {
-        #method_vis fn #method_name( #( #args, )* ) -> Self {
-            Self(
-                // SAFETY: We're combining valid values from `into_raw()` that by
-                // `BitFieldCompatible`'s preconditions guaranteed to return valid
-                // discriminants.
-                unsafe { ::superbitty::Raw::new(( 0 #( | #fields_calculation )* ) as #base_ty) },
-            )
+        #method_vis #const_token fn #method_name( #( #args, )* ) -> Self {
+            // SAFETY: We're combining valid values from `into_raw()` that by
+            // `BitFieldCompatible`'s preconditions guaranteed to return valid
+            // discriminants.
+            unsafe {
+                let mut raw = ::superbitty::Raw::<#base_ty>::zeroed();
+                #( #fields_calculation )*
+                Self(raw)
+            }
         }
     }
 }
 
-fn bitfield_accessors(
-    Bitfield {
-        parse: parse::BitfieldsStructField { attrs, vis, ident: field_name, ty },
-        bit_offset,
-        type_shift,
-        bits_mask,
-        is_last,
-    }: &Bitfield,
+fn from_raw_methods(
+    bitfields: &[Bitfield],
+    kept_reserved_bits: &[(TokenStream, TokenStream)],
+    method_vis: &syn::Visibility,
     base_ty: &syn::Type,
 ) -> TokenStream {
-    let setter_name = format_ident!("set_{field_name}");
-    let mut getter_stripped_field =
-        quote! { ((::superbitty::Raw::raw(self.0) as u128) >> #bit_offset) };
-    if !is_last {
-        getter_stripped_field = quote! { (#getter_stripped_field & #bits_mask) }
+    let fields_bits = bitfields
+        .iter()
+        .map(|Bitfield { bit_offset, bits_len, .. }| (bit_offset, bits_len))
+        .chain(kept_reserved_bits.iter().map(|(bit_offset, bits_len)| (bit_offset, bits_len)));
+    let clear_fields = fields_bits.map(|(bit_offset, bits_len)| {
+        quote! { __rest.set_bits(#bit_offset, #bits_len, 0); }
+    });
+    let fields_checks = bitfields.iter().map(
+        |Bitfield { parse: parse::BitfieldsStructField { ty, .. }, bit_offset, type_shift, bits_len }| {
+            quote! {
+                if !<#ty as ::superbitty::BitFieldCompatible>::is_valid_raw(
+                    __rest.get_bits(#bit_offset, #bits_len) << #type_shift,
+                ) {
+                    return ::superbitty::__helpers::Result::Err(::superbitty::__helpers::INVALID_BITS);
+                }
+            }
+        },
+    );
+    quote! {
+        /// Decodes the bitfields from their raw representation, checking that all fields
+        /// hold valid values and that all bits outside the fields (except reserved bits
+        /// marked with `#[reserved(keep)]`) are zero.
+        #[allow(dead_code)]
+        #method_vis fn from_raw(raw: #base_ty) -> ::superbitty::__helpers::Result<Self, ::superbitty::InvalidBits> {
+            // SAFETY: This is only used to inspect the bits, and the fields are checked
+            // before it is used to create `Self`.
+            let mut __rest = unsafe { ::superbitty::Raw::new(raw) };
+            #(#fields_checks)*
+            // SAFETY: As above.
+            unsafe {
+                #(#clear_fields)*
+            }
+            if !__rest.is_zero() {
+                return ::superbitty::__helpers::Result::Err(::superbitty::__helpers::INVALID_BITS);
+            }
+            // SAFETY: We just checked that all fields are valid.
+            ::superbitty::__helpers::Result::Ok(unsafe { Self::from_raw_unchecked(raw) })
+        }
+
+        /// Creates the bitfields from their raw representation without checking it.
+        ///
+        /// # Safety
+        ///
+        /// `raw` must be valid, i.e. [`Self::from_raw()`] must succeed on it.
+        #[allow(dead_code)]
+        #method_vis unsafe fn from_raw_unchecked(raw: #base_ty) -> Self {
+            // SAFETY: Guaranteed by the caller.
+            Self(unsafe { ::superbitty::Raw::new(raw) })
+        }
     }
+}
+
+/// The bytes are those of the raw value as a single integer, even for array base types.
+fn bytes_methods(
+    vis: &syn::Visibility,
+    method_vis: &syn::Visibility,
+    base_ty: &syn::Type,
+) -> TokenStream {
+    let bytes_len = quote! { ::superbitty::__helpers::size_of::<#base_ty>() };
+    quote! {
+        /// Returns the raw value as bytes in little-endian order.
+        #[inline]
+        #[allow(dead_code)]
+        #vis fn to_le_bytes(&self) -> [::core::primitive::u8; #bytes_len] {
+            let mut bytes = [0; #bytes_len];
+            let mut i = 0;
+            while i < #bytes_len {
+                bytes[i] = self.0.get_bits(i as ::core::primitive::u32 * 8, 8) as ::core::primitive::u8;
+                i += 1;
+            }
+            bytes
+        }
+
+        /// Returns the raw value as bytes in big-endian order.
+        #[inline]
+        #[allow(dead_code)]
+        #vis fn to_be_bytes(&self) -> [::core::primitive::u8; #bytes_len] {
+            let mut bytes = self.to_le_bytes();
+            bytes.reverse();
+            bytes
+        }
+
+        /// Returns the raw value as bytes in native order.
+        #[inline]
+        #[allow(dead_code)]
+        #vis fn to_ne_bytes(&self) -> [::core::primitive::u8; #bytes_len] {
+            if cfg!(target_endian = "big") {
+                self.to_be_bytes()
+            } else {
+                self.to_le_bytes()
+            }
+        }
+
+        /// Decodes the bitfields from bytes in little-endian order, checking them like
+        /// [`Self::from_raw()`].
+        #[inline]
+        #[allow(dead_code)]
+        #method_vis fn from_le_bytes(
+            bytes: [::core::primitive::u8; #bytes_len],
+        ) -> ::superbitty::__helpers::Result<Self, ::superbitty::InvalidBits> {
+            // SAFETY: This is only used to build the raw value, which is then checked.
+            let raw = unsafe {
+                let mut raw = ::superbitty::Raw::<#base_ty>::zeroed();
+                let mut i = 0;
+                while i < #bytes_len {
+                    raw.set_bits(i as ::core::primitive::u32 * 8, 8, bytes[i] as ::core::primitive::u128);
+                    i += 1;
+                }
+                raw
+            };
+            Self::from_raw(::superbitty::Raw::raw(raw))
+        }
+
+        /// Decodes the bitfields from bytes in big-endian order, checking them like
+        /// [`Self::from_raw()`].
+        #[inline]
+        #[allow(dead_code)]
+        #method_vis fn from_be_bytes(
+            mut bytes: [::core::primitive::u8; #bytes_len],
+        ) -> ::superbitty::__helpers::Result<Self, ::superbitty::InvalidBits> {
+            bytes.reverse();
+            Self::from_le_bytes(bytes)
+        }
+
+        /// Decodes the bitfields from bytes in native order, checking them like
+        /// [`Self::from_raw()`].
+        #[inline]
+        #[allow(dead_code)]
+        #method_vis fn from_ne_bytes(
+            bytes: [::core::primitive::u8; #bytes_len],
+        ) -> ::superbitty::__helpers::Result<Self, ::superbitty::InvalidBits> {
+            if cfg!(target_endian = "big") {
+                Self::from_be_bytes(bytes)
+            } else {
+                Self::from_le_bytes(bytes)
+            }
+        }
+    }
+}
+
+fn bitfield_accessors(bitfield: &Bitfield, const_token: Option<syn::Token![const]>) -> TokenStream {
+    let Bitfield {
+        parse: parse::BitfieldsStructField { attrs, vis, ident: field_name, ty, .. },
+        bit_offset,
+        type_shift,
+        bits_len,
+    } = bitfield;
+    let getter_name = bitfield.parse.getter_name();
+    let setter_name = format_ident!("set_{getter_name}");
+    let with_name = format_ident!("with_{getter_name}");
+    let element_accessors = match ty {
+        syn::Type::Array(array) => Some(array_element_accessors(bitfield, array, const_token)),
+        _ => None,
+    };
+    let field = field_from_raw(
+        ty,
+        quote!(self.0.get_bits(#bit_offset, #bits_len) << #type_shift),
+        const_token,
+    );
+    let raw_field = field_into_raw(ty, quote!(value), const_token);
     quote_spanned! {field_name.span()=>
         #(#attrs)* // We put the attributes on the getter mainly for documentation comments.
         #[inline]
-        #vis fn #field_name(&self) -> #ty {
+        #[allow(unused_unsafe)] // Not all const conversions need `unsafe`.
+        #vis #const_token fn #getter_name(&self) -> #ty {
             // SAFETY: Since `self.0` always holds valid instances, and all bitfields are
             // `Copy`, we can convert the bitfield to its enum soundly.
-            unsafe {
-                <#ty as ::superbitty::BitFieldCompatible>::from_raw(
-                    #getter_stripped_field << #type_shift,
-                )
-            }
+            unsafe { #field }
         }
 
         #[inline]
         #[allow(dead_code)] // User may use the constructor and getters only.
-        #vis fn #setter_name(&mut self, value: #ty) {
-            let raw_without_field = (::superbitty::Raw::raw(self.0) as u128) & !(#bits_mask << #bit_offset);
-            let field_in_place =
-                (<#ty as ::superbitty::BitFieldCompatible>::into_raw(value) >> #type_shift)
-                    << #bit_offset;
+        #vis #const_token fn #setter_name(&mut self, value: #ty) {
+            let raw_field = #raw_field >> #type_shift;
             // SAFETY: We only trim irrelevant bits that by `BitFieldCompatible`'s precondition
             // should be safe.
-            *unsafe { ::superbitty::Raw::get_mut(&mut self.0) } =
-                (raw_without_field | field_in_place) as #base_ty;
+            unsafe { self.0.set_bits(#bit_offset, #bits_len, raw_field) };
+        }
+
+        #[inline]
+        #[must_use]
+        #[allow(dead_code)]
+        #vis #const_token fn #with_name(mut self, value: #ty) -> Self {
+            self.#setter_name(value);
+            self
+        }
+
+        #element_accessors
+    }
+}
+
+/// Accessors for a field of a non-primary view. Since the bits may not be valid for it,
+/// the getter returns `None` in that case, and since the struct must stay valid for the
+/// primary view, the setters fail if the new value would make it invalid. They check the
+/// validity with trait methods, so they are never `const`.
+fn alternative_accessors(
+    Alternative {
+        bitfield:
+            Bitfield {
+                parse: parse::BitfieldsStructField { attrs, vis, ident: field_name, ty, .. },
+                bit_offset,
+                type_shift,
+                bits_len,
+            },
+        primary_valid,
+    }: &Alternative,
+) -> TokenStream {
+    let setter_name = format_ident!("set_{field_name}");
+    let with_name = format_ident!("with_{field_name}");
+    quote_spanned! {field_name.span()=>
+        #(#attrs)*
+        #[inline]
+        #vis fn #field_name(&self) -> ::superbitty::__helpers::Option<#ty> {
+            <#ty as ::superbitty::BitFieldCompatible>::try_from_raw(
+                self.0.get_bits(#bit_offset, #bits_len) << #type_shift,
+            )
+        }
+
+        #[inline]
+        #[allow(dead_code)]
+        #vis fn #setter_name(
+            &mut self,
+            value: #ty,
+        ) -> ::superbitty::__helpers::Result<(), ::superbitty::InvalidBits> {
+            let mut __raw = self.0;
+            let raw_field =
+                <#ty as ::superbitty::BitFieldCompatible>::into_raw(value) >> #type_shift;
+            // SAFETY: We only store it in `self` if it is valid.
+            unsafe { __raw.set_bits(#bit_offset, #bits_len, raw_field) };
+            let __raw = &__raw;
+            if !#primary_valid {
+                return ::superbitty::__helpers::Result::Err(::superbitty::__helpers::INVALID_BITS);
+            }
+            self.0 = *__raw;
+            ::superbitty::__helpers::Result::Ok(())
+        }
+
+        #[inline]
+        #[allow(dead_code)]
+        #vis fn #with_name(
+            mut self,
+            value: #ty,
+        ) -> ::superbitty::__helpers::Result<Self, ::superbitty::InvalidBits> {
+            match self.#setter_name(value) {
+                ::superbitty::__helpers::Result::Ok(()) => ::superbitty::__helpers::Result::Ok(self),
+                ::superbitty::__helpers::Result::Err(error) => ::superbitty::__helpers::Result::Err(error),
+            }
+        }
+    }
+}
+
+/// `is_<view>_valid()`, that checks whether the bits are valid for the view.
+fn view_method(ViewInfo { ident, valid }: &ViewInfo, vis: &syn::Visibility) -> TokenStream {
+    let method_name = format_ident!("is_{ident}_valid");
+    let doc = format!("Whether the bits of the union are valid for the `{ident}` view.");
+    quote_spanned! {ident.span()=>
+        #[doc = #doc]
+        #[inline]
+        #[allow(dead_code)]
+        #vis fn #method_name(&self) -> ::core::primitive::bool {
+            let __raw = &self.0;
+            #valid
+        }
+    }
+}
+
+/// Accessors for a single element of an array field, named after the field.
+/// Element 0 is at the least significant end of the field even with `msb0`, to match the
+/// layout of the whole array.
+fn array_element_accessors(
+    Bitfield {
+        parse: parse::BitfieldsStructField { attrs, vis, ident: field_name, .. },
+        bit_offset,
+        ..
+    }: &Bitfield,
+    array: &syn::TypeArray,
+    const_token: Option<syn::Token![const]>,
+) -> TokenStream {
+    let setter_name = format_ident!("set_{field_name}");
+    let with_name = format_ident!("with_{field_name}");
+    let element_ty = &array.elem;
+    let len = &array.len;
+    let element = quote!(<#element_ty as ::superbitty::BitFieldCompatible>);
+    let element_value =
+        field_from_raw(element_ty, quote!(raw_element << #element::SHIFT), const_token);
+    let raw_element = field_into_raw(element_ty, quote!(value), const_token);
+    // Indexing panics with the same message as a slice would, even in a `const fn`.
+    let check_index = quote! {
+        #[allow(clippy::no_effect)]
+        [(); #len][index];
+    };
+    quote_spanned! {field_name.span()=>
+        #(#attrs)*
+        #[inline]
+        #[track_caller]
+        #[allow(dead_code, unused_unsafe)]
+        #vis #const_token fn #field_name(&self, index: ::core::primitive::usize) -> #element_ty {
+            #check_index
+            let element_offset = #bit_offset + index as ::core::primitive::u32 * #element::BITS_LEN;
+            let raw_element = self.0.get_bits(element_offset, #element::BITS_LEN);
+            // SAFETY: Since `self.0` always holds valid instances, every element is valid.
+            unsafe { #element_value }
+        }
+
+        #[inline]
+        #[track_caller]
+        #[allow(dead_code)]
+        #vis #const_token fn #setter_name(&mut self, index: ::core::primitive::usize, value: #element_ty) {
+            #check_index
+            let element_offset = #bit_offset + index as ::core::primitive::u32 * #element::BITS_LEN;
+            let raw_element = #raw_element >> #element::SHIFT;
+            // SAFETY: We only replace the bits of this element with another valid value.
+            unsafe { self.0.set_bits(element_offset, #element::BITS_LEN, raw_element) };
+        }
+
+        #[inline]
+        #[must_use]
+        #[track_caller]
+        #[allow(dead_code)]
+        #vis #const_token fn #with_name(mut self, index: ::core::primitive::usize, value: #element_ty) -> Self {
+            self.#setter_name(index, value);
+            self
         }
     }
 }
@@ -212,15 +886,23 @@ fn verify_base_ty(base_ty: &syn::Type) -> syn::Result<()> {
     if is_unsigned_int_primitive(base_ty) {
         return Ok(());
     }
+    if let syn::Type::Array(syn::TypeArray { elem, .. }) = base_ty {
+        if let syn::Type::Path(syn::TypePath { qself: None, path }) = &**elem {
+            if path.is_ident("u8") || path.is_ident("u32") || path.is_ident("u64") {
+                return Ok(());
+            }
+        }
+    }
 
-    return Err(syn::Error::new_spanned(
+    Err(syn::Error::new_spanned(
         base_ty,
-        "unsupported base type for `bitfields!`: only primitive unsigned types are supported",
-    ));
+        "unsupported base type for `bitfields!`: only primitive unsigned types and arrays of \
+            `u8`, `u32` or `u64` are supported",
+    ))
 }
 
 fn assert_bitfields_compatible(fields: &parse::BitfieldsStructFields) -> TokenStream {
-    let field_asserts = fields.fields.iter().map(|parse::BitfieldsStructField { ty, .. }| {
+    let field_asserts = fields.all_fields().map(|parse::BitfieldsStructField { ty, .. }| {
         quote_spanned! {ty.span()=>
             ::superbitty::__helpers::assert_bitfield_compatible::<#ty>();
         }
@@ -231,3 +913,28 @@ fn assert_bitfields_compatible(fields: &parse::BitfieldsStructFields) -> TokenSt
         };
     }
 }
+
+/// Converts `value` of the field type `ty` to its raw value, in a way that can be used in
+/// `const fn`s if the struct is `#[bitfields(const)]`.
+fn field_into_raw(
+    ty: &syn::Type,
+    value: TokenStream,
+    const_token: Option<syn::Token![const]>,
+) -> TokenStream {
+    match const_token {
+        Some(_) => crate::const_raw::into_raw(ty, value),
+        None => quote!(<#ty as ::superbitty::BitFieldCompatible>::into_raw(#value)),
+    }
+}
+
+/// Like [`field_into_raw()`], but the other way. Must be used in an `unsafe` context.
+fn field_from_raw(
+    ty: &syn::Type,
+    raw: TokenStream,
+    const_token: Option<syn::Token![const]>,
+) -> TokenStream {
+    match const_token {
+        Some(_) => crate::const_raw::from_raw(ty, raw),
+        None => quote!(<#ty as ::superbitty::BitFieldCompatible>::from_raw(#raw)),
+    }
+}
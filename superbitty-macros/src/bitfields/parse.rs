@@ -4,6 +4,11 @@ use syn::*;
 
 mod kw {
     syn::custom_keyword!(construct);
+    syn::custom_keyword!(zero);
+    syn::custom_keyword!(keep);
+    syn::custom_keyword!(order);
+    syn::custom_keyword!(lsb0);
+    syn::custom_keyword!(msb0);
 }
 
 pub(super) type Constructor = Option<(Visibility, Ident)>;
@@ -17,11 +22,23 @@ pub(super) struct BitfieldsStruct {
     pub(super) generics: Generics,
     pub(super) fields: BitfieldsStructFields,
     pub(super) constructor: Constructor,
+    pub(super) order: BitOrder,
+    pub(super) const_token: Option<Token![const]>,
+}
+
+/// The order in which fields are allocated, and in which explicit positions are numbered.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum BitOrder {
+    /// From the least significant bit up, the default.
+    Lsb0,
+    /// From the most significant bit down.
+    Msb0,
 }
 
 impl Parse for BitfieldsStruct {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let attrs = input.call(Attribute::parse_outer)?;
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let (order, const_token) = parse_options(&mut attrs)?;
         let vis = input.parse()?;
         let struct_token = input.parse()?;
         let ident = input.parse()?;
@@ -58,19 +75,294 @@ impl Parse for BitfieldsStruct {
             generics: Generics { where_clause, ..generics },
             fields,
             constructor,
+            order,
+            const_token,
         })
     }
 }
 
+/// Removes `#[bitfields(…)]` from `attrs`, and parses the options in it: `order = …`
+/// and `const`.
+fn parse_options(attrs: &mut Vec<Attribute>) -> Result<(BitOrder, Option<Token![const]>)> {
+    let mut order = None;
+    let mut const_token = None;
+    let mut errors = crate::utils::SynErrors::default();
+    attrs.retain(|attr| {
+        if !attr.path.is_ident("bitfields") {
+            return true;
+        }
+        let parsed = attr.parse_args_with(|input: ParseStream<'_>| {
+            let mut options = Vec::new();
+            loop {
+                let lookahead = input.lookahead1();
+                if lookahead.peek(kw::order) {
+                    input.parse::<kw::order>()?;
+                    input.parse::<Token![=]>()?;
+                    let lookahead = input.lookahead1();
+                    if lookahead.peek(kw::lsb0) {
+                        input.parse::<kw::lsb0>()?;
+                        options.push(BitfieldsOption::Order(BitOrder::Lsb0));
+                    } else if lookahead.peek(kw::msb0) {
+                        input.parse::<kw::msb0>()?;
+                        options.push(BitfieldsOption::Order(BitOrder::Msb0));
+                    } else {
+                        return Err(lookahead.error());
+                    }
+                } else if lookahead.peek(Token![const]) {
+                    options.push(BitfieldsOption::Const(input.parse()?));
+                } else {
+                    return Err(lookahead.error());
+                }
+                if input.is_empty() {
+                    return Ok(options);
+                }
+                input.parse::<Token![,]>()?;
+            }
+        });
+        match parsed {
+            Ok(options) => {
+                for option in options {
+                    match option {
+                        BitfieldsOption::Order(_) if order.is_some() => errors
+                            .push(Error::new_spanned(attr, "the order can only be specified once")),
+                        BitfieldsOption::Order(parsed) => order = Some(parsed),
+                        BitfieldsOption::Const(token) if const_token.is_some() => errors
+                            .push(Error::new(token.span, "`const` can only be specified once")),
+                        BitfieldsOption::Const(token) => const_token = Some(token),
+                    }
+                }
+            }
+            Err(err) => errors.push(err),
+        }
+        false
+    });
+    errors.into_result()?;
+    Ok((order.unwrap_or(BitOrder::Lsb0), const_token))
+}
+
+enum BitfieldsOption {
+    Order(BitOrder),
+    Const(Token![const]),
+}
+
 pub(super) struct BitfieldsStructFields {
-    pub(super) fields: Punctuated<BitfieldsStructField, Token![,]>,
+    pub(super) entries: Punctuated<BitfieldsStructEntry, Token![,]>,
 }
 
 impl Parse for BitfieldsStructFields {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let content;
         braced!(content in input);
-        Ok(Self { fields: content.parse_terminated(BitfieldsStructField::parse)? })
+        Ok(Self { entries: content.parse_terminated(BitfieldsStructEntry::parse)? })
+    }
+}
+
+impl BitfieldsStructFields {
+    /// The named fields that take part in the constructor and the derives: all fields
+    /// except the reserved bits and the fields of non-primary views.
+    pub(super) fn fields(&self) -> impl Iterator<Item = &BitfieldsStructField> {
+        self.entries.iter().flat_map(|entry| -> Box<dyn Iterator<Item = _>> {
+            match entry {
+                BitfieldsStructEntry::Field(field) => Box::new(std::iter::once(field)),
+                BitfieldsStructEntry::Reserved(_) => Box::new(std::iter::empty()),
+                BitfieldsStructEntry::Union(union) => Box::new(union.primary_view().fields()),
+            }
+        })
+    }
+
+    /// All named fields, including those of non-primary views.
+    pub(super) fn all_fields(&self) -> impl Iterator<Item = &BitfieldsStructField> {
+        self.entries.iter().flat_map(|entry| -> Box<dyn Iterator<Item = _>> {
+            match entry {
+                BitfieldsStructEntry::Field(field) => Box::new(std::iter::once(field)),
+                BitfieldsStructEntry::Reserved(_) => Box::new(std::iter::empty()),
+                BitfieldsStructEntry::Union(union) => {
+                    Box::new(union.views.iter().flat_map(View::fields))
+                }
+            }
+        })
+    }
+}
+
+pub(super) enum BitfieldsStructEntry {
+    Field(BitfieldsStructField),
+    Reserved(ReservedBits),
+    Union(BitfieldsUnion),
+}
+
+impl BitfieldsStructEntry {
+    pub(super) fn position(&self) -> Option<&Position> {
+        match self {
+            BitfieldsStructEntry::Field(field) => field.position.as_ref(),
+            BitfieldsStructEntry::Reserved(reserved) => reserved.position.as_ref(),
+            BitfieldsStructEntry::Union(union) => union.position.as_ref(),
+        }
+    }
+}
+
+/// Alternative views of the same bits, written as `union { view { fields… }, … }`.
+pub(super) struct BitfieldsUnion {
+    pub(super) union_token: Token![union],
+    pub(super) views: Vec<View>,
+    /// The index of the view marked with `#[primary]`, or the first view. It is the view
+    /// the struct is guaranteed to be valid for, and the one used by the constructor and
+    /// the derives.
+    pub(super) primary: usize,
+    pub(super) position: Option<Position>,
+}
+
+impl BitfieldsUnion {
+    pub(super) fn primary_view(&self) -> &View {
+        &self.views[self.primary]
+    }
+}
+
+pub(super) struct View {
+    pub(super) ident: Ident,
+    pub(super) entries: Punctuated<BitfieldsStructEntry, Token![,]>,
+}
+
+impl View {
+    fn fields(&self) -> impl Iterator<Item = &BitfieldsStructField> {
+        self.entries.iter().filter_map(|entry| match entry {
+            BitfieldsStructEntry::Field(field) => Some(field),
+            _ => None,
+        })
+    }
+}
+
+fn parse_union(
+    input: ParseStream<'_>,
+    attrs: Vec<Attribute>,
+    position: Option<Position>,
+) -> Result<BitfieldsUnion> {
+    if let Some(attr) = attrs.iter().find(|attr| !attr.path.is_ident("doc")) {
+        return Err(Error::new_spanned(attr, "unions can only have doc comments"));
+    }
+    let union_token = input.parse::<Token![union]>()?;
+    let content;
+    braced!(content in input);
+    let mut views = Vec::new();
+    let mut primary = None;
+    let mut errors = crate::utils::SynErrors::default();
+    while !content.is_empty() {
+        let view_attrs = content.call(Attribute::parse_outer)?;
+        let ident = content.parse::<Ident>()?;
+        for attr in view_attrs {
+            if attr.path.is_ident("primary") && attr.tokens.is_empty() {
+                if primary.is_some() {
+                    errors.push(Error::new_spanned(attr, "only one view can be `#[primary]`"));
+                }
+                primary = Some(views.len());
+            } else if !attr.path.is_ident("doc") {
+                errors.push(Error::new_spanned(
+                    attr,
+                    "views can only have doc comments and `#[primary]`",
+                ));
+            }
+        }
+        let view_content;
+        braced!(view_content in content);
+        let entries = view_content.parse_terminated(BitfieldsStructEntry::parse)?;
+        for entry in &entries {
+            match entry {
+                BitfieldsStructEntry::Union(union) => errors.push(Error::new_spanned(
+                    union.union_token,
+                    "unions cannot be nested in views",
+                )),
+                _ if entry.position().is_some() => errors.push(Error::new_spanned(
+                    &ident,
+                    "the entries of a view cannot have explicit positions",
+                )),
+                _ => {}
+            }
+        }
+        views.push(View { ident, entries });
+        if content.is_empty() {
+            break;
+        }
+        content.parse::<Token![,]>()?;
+    }
+    if views.is_empty() {
+        errors.push(Error::new_spanned(union_token, "a union must have at least one view"));
+    }
+    errors.into_result()?;
+    let primary = primary.unwrap_or(0);
+    if let Some(array) =
+        views.iter().enumerate().filter(|&(index, _)| index != primary).find_map(|(_, view)| {
+            view.fields().find_map(|field| match &field.ty {
+                syn::Type::Array(array) => Some(array),
+                _ => None,
+            })
+        })
+    {
+        return Err(Error::new_spanned(
+            array,
+            "array fields are only supported in the primary view",
+        ));
+    }
+    Ok(BitfieldsUnion { union_token, views, primary, position })
+}
+
+/// Bits that take up space in the layout but have no accessors, written as `_: width`.
+pub(super) struct ReservedBits {
+    pub(super) underscore: Token![_],
+    pub(super) width: Expr,
+    /// Whether the bits may hold any value (`#[reserved(keep)]`), instead of
+    /// being required to be zero (`#[reserved(zero)]`, the default).
+    pub(super) keep: bool,
+    pub(super) position: Option<Position>,
+}
+
+impl Parse for BitfieldsStructEntry {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let position = parse_position(&mut attrs)?;
+        if input.peek(Token![union]) && input.peek2(token::Brace) {
+            return parse_union(input, attrs, position).map(Self::Union);
+        }
+        let reserved_attr = attrs.iter().position(|attr| attr.path.is_ident("reserved"));
+        if !input.peek(Token![_]) {
+            if let Some(reserved_attr) = reserved_attr {
+                return Err(Error::new_spanned(
+                    &attrs[reserved_attr],
+                    "`#[reserved]` can only be used on `_` entries",
+                ));
+            }
+            let vis = input.parse()?;
+            let ident = input.parse()?;
+            input.parse::<Token![:]>()?;
+            let ty = input.parse()?;
+            return Ok(Self::Field(BitfieldsStructField { attrs, vis, ident, ty, position }));
+        }
+
+        let underscore = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let width = input.parse()?;
+        let mut keep = false;
+        for attr in attrs {
+            if attr.path.is_ident("doc") {
+                continue;
+            }
+            if !attr.path.is_ident("reserved") {
+                return Err(Error::new_spanned(attr, "reserved bits can only have doc comments"));
+            }
+            if !attr.tokens.is_empty() {
+                keep = attr.parse_args_with(|input: ParseStream<'_>| {
+                    let lookahead = input.lookahead1();
+                    if lookahead.peek(kw::keep) {
+                        input.parse::<kw::keep>()?;
+                        Ok(true)
+                    } else if lookahead.peek(kw::zero) {
+                        input.parse::<kw::zero>()?;
+                        Ok(false)
+                    } else {
+                        Err(lookahead.error())
+                    }
+                })?;
+            }
+        }
+        Ok(Self::Reserved(ReservedBits { underscore, width, keep, position }))
     }
 }
 
@@ -79,15 +371,78 @@ pub(super) struct BitfieldsStructField {
     pub(super) vis: Visibility,
     pub(super) ident: Ident,
     pub(super) ty: syn::Type,
+    pub(super) position: Option<Position>,
 }
 
-impl Parse for BitfieldsStructField {
-    fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let attrs = input.call(Attribute::parse_outer)?;
-        let vis = input.parse()?;
-        let ident = input.parse()?;
-        input.parse::<Token![:]>()?;
-        let ty = input.parse()?;
-        Ok(Self { attrs, vis, ident, ty })
+/// An explicit position given with `#[bits(start..end)]` or `#[bit(index)]`.
+pub(super) struct Position {
+    pub(super) start: proc_macro2::TokenStream,
+    /// Exclusive.
+    pub(super) end: proc_macro2::TokenStream,
+}
+
+/// Removes `#[bits(…)]` and `#[bit(…)]` from `attrs`, and parses them.
+fn parse_position(attrs: &mut Vec<Attribute>) -> Result<Option<Position>> {
+    let mut position = None;
+    let mut errors = crate::utils::SynErrors::default();
+    attrs.retain(|attr| {
+        let is_bits = attr.path.is_ident("bits");
+        if !is_bits && !attr.path.is_ident("bit") {
+            return true;
+        }
+        if position.is_some() {
+            errors.push(Error::new_spanned(attr, "a field can only have one position"));
+            return false;
+        }
+        let parsed = if is_bits {
+            attr.parse_args_with(parse_range)
+        } else {
+            attr.parse_args::<Expr>().map(|index| Position {
+                start: quote::quote!(#index),
+                end: quote::quote!((#index) + 1),
+            })
+        };
+        match parsed {
+            Ok(parsed) => position = Some(parsed),
+            Err(err) => errors.push(err),
+        }
+        false
+    });
+    errors.into_result()?;
+    Ok(position)
+}
+
+/// Parses `start..end` or `start..=end`. We don't enable syn's `full` feature, so we
+/// cannot parse an `ExprRange`.
+fn parse_range(input: ParseStream<'_>) -> Result<Position> {
+    let mut start = proc_macro2::TokenStream::new();
+    while !input.is_empty() && !input.peek(Token![..]) {
+        start.extend([input.parse::<proc_macro2::TokenTree>()?]);
+    }
+    let end = if input.peek(Token![..=]) {
+        input.parse::<Token![..=]>()?;
+        let end = input.parse::<proc_macro2::TokenStream>()?;
+        (!end.is_empty()).then(|| quote::quote!((#end) + 1))
+    } else if input.peek(Token![..]) {
+        input.parse::<Token![..]>()?;
+        let end = input.parse::<proc_macro2::TokenStream>()?;
+        (!end.is_empty()).then_some(end)
+    } else {
+        None
+    };
+    match end {
+        Some(end) if !start.is_empty() => Ok(Position { start, end }),
+        _ => Err(input.error("expected a range with both ends: `#[bits(start..end)]`")),
+    }
+}
+
+impl BitfieldsStructField {
+    /// The getter of the whole field. For arrays, the plain name is used for the
+    /// getter of a single element, so this is `<name>_array`.
+    pub(super) fn getter_name(&self) -> Ident {
+        match &self.ty {
+            syn::Type::Array(_) => quote::format_ident!("{}_array", self.ident),
+            _ => self.ident.clone(),
+        }
     }
 }
@@ -0,0 +1,222 @@
+//! Conversions between `BitFieldCompatible` types and their raw values that can be used
+//! in `const fn`s, where trait methods cannot be called.
+//!
+//! Types from `core` are converted inline, mirroring their `BitFieldCompatible` impls.
+//! Every other type must have the hidden inherent `__superbitty_into_raw()` and
+//! `__superbitty_from_raw()`, which `#[derive(BitFieldCompatible)]`, `bitfields!` and
+//! the types of `superbitty` have.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+/// An expression converting `value` of type `ty` to its raw value, like
+/// `BitFieldCompatible::into_raw()`.
+pub(crate) fn into_raw(ty: &syn::Type, value: TokenStream) -> TokenStream {
+    let compatible = quote!(::superbitty::BitFieldCompatible);
+    match ty {
+        syn::Type::Paren(syn::TypeParen { elem, .. })
+        | syn::Type::Group(syn::TypeGroup { elem, .. }) => into_raw(elem, value),
+        syn::Type::Array(syn::TypeArray { elem, len, .. }) => {
+            let element = into_raw(elem, quote!(__array[__index]));
+            quote! {
+                {
+                    let __array: #ty = #value;
+                    let mut __raw = 0;
+                    let mut __index = 0;
+                    while __index < #len {
+                        __raw |= (#element >> <#elem as #compatible>::SHIFT)
+                            << (__index as ::core::primitive::u32 * <#elem as #compatible>::BITS_LEN);
+                        __index += 1;
+                    }
+                    __raw
+                }
+            }
+        }
+        syn::Type::Tuple(tuple) if !tuple.elems.is_empty() => {
+            let elements = tuple.elems.iter().enumerate().map(|(index, elem)| {
+                let index = syn::Index::from(index);
+                let element = into_raw(elem, quote!(__tuple.#index));
+                let previous = tuple.elems.iter().take(index.index as usize);
+                // Zero-width elements may start right after the 128th bit.
+                quote! {
+                    | match (#element >> <#elem as #compatible>::SHIFT)
+                        .checked_shl(0 #( + <#previous as #compatible>::BITS_LEN )*)
+                    {
+                        ::superbitty::__helpers::Some(__element) => __element,
+                        ::superbitty::__helpers::None => 0,
+                    }
+                }
+            });
+            quote! {
+                {
+                    let __tuple: #ty = #value;
+                    0 #(#elements)*
+                }
+            }
+        }
+        _ => match core_type(ty) {
+            Some(CoreType::Primitive(primitive)) => quote! {
+                {
+                    let __value: ::core::primitive::#primitive = #value;
+                    __value as ::core::primitive::u128
+                }
+            },
+            Some(CoreType::NonZero(non_zero)) => quote! {
+                {
+                    let __value: ::core::num::#non_zero = #value;
+                    __value.get() as ::core::primitive::u128
+                }
+            },
+            Some(CoreType::Option(inner)) => {
+                let some = into_raw(inner, quote!(__inner));
+                quote! {
+                    {
+                        let __option: ::superbitty::__helpers::Option<#inner> = #value;
+                        match (__option, <#inner as #compatible>::NICHE) {
+                            (::superbitty::__helpers::Some(__inner), ::superbitty::__helpers::Some(_)) => {
+                                #some
+                            }
+                            (::superbitty::__helpers::None, ::superbitty::__helpers::Some(__niche)) => __niche,
+                            (::superbitty::__helpers::Some(__inner), ::superbitty::__helpers::None) => {
+                                (#some)
+                                    | 1 << (<#inner as #compatible>::SHIFT
+                                        + <#inner as #compatible>::BITS_LEN)
+                            }
+                            (::superbitty::__helpers::None, ::superbitty::__helpers::None) => 0,
+                        }
+                    }
+                }
+            }
+            None => quote_spanned!(ty.span()=> <#ty>::__superbitty_into_raw(#value)),
+        },
+    }
+}
+
+/// An expression converting the raw value `raw` back to type `ty`, like
+/// `BitFieldCompatible::from_raw()`. It must be used in an `unsafe` context, and `raw` must
+/// be valid for `ty`.
+pub(crate) fn from_raw(ty: &syn::Type, raw: TokenStream) -> TokenStream {
+    let compatible = quote!(::superbitty::BitFieldCompatible);
+    match ty {
+        syn::Type::Paren(syn::TypeParen { elem, .. })
+        | syn::Type::Group(syn::TypeGroup { elem, .. }) => from_raw(elem, raw),
+        syn::Type::Array(syn::TypeArray { elem, len, .. }) => {
+            let element = from_raw(
+                elem,
+                quote! {
+                    ((__raw >> (__index as ::core::primitive::u32 * <#elem as #compatible>::BITS_LEN))
+                        & ::superbitty::__helpers::low_bits_mask(<#elem as #compatible>::BITS_LEN))
+                        << <#elem as #compatible>::SHIFT
+                },
+            );
+            quote! {
+                {
+                    let __raw: ::core::primitive::u128 = #raw;
+                    let mut __array = ::superbitty::__helpers::MaybeUninit::<#ty>::uninit();
+                    let mut __index = 0;
+                    while __index < #len {
+                        let __element = #element;
+                        __array.as_mut_ptr().cast::<#elem>().add(__index).write(__element);
+                        __index += 1;
+                    }
+                    __array.assume_init()
+                }
+            }
+        }
+        syn::Type::Tuple(tuple) if !tuple.elems.is_empty() => {
+            let elements = tuple.elems.iter().enumerate().map(|(index, elem)| {
+                let previous = tuple.elems.iter().take(index);
+                from_raw(
+                    elem,
+                    quote! {
+                        (match __raw.checked_shr(0 #( + <#previous as #compatible>::BITS_LEN )*) {
+                            ::superbitty::__helpers::Some(__element) => __element,
+                            ::superbitty::__helpers::None => 0,
+                        } & ::superbitty::__helpers::low_bits_mask(<#elem as #compatible>::BITS_LEN))
+                            << <#elem as #compatible>::SHIFT
+                    },
+                )
+            });
+            quote! {
+                {
+                    let __raw: ::core::primitive::u128 = #raw;
+                    ( #( #elements, )* )
+                }
+            }
+        }
+        _ => match core_type(ty) {
+            Some(CoreType::Primitive(primitive)) if *primitive == "bool" => quote!((#raw) != 0),
+            Some(CoreType::Primitive(primitive)) if *primitive == "char" => {
+                quote!(::core::char::from_u32_unchecked((#raw) as ::core::primitive::u32))
+            }
+            Some(CoreType::Primitive(primitive)) => quote!((#raw) as ::core::primitive::#primitive),
+            Some(CoreType::NonZero(non_zero)) => {
+                let primitive = syn::Ident::new(
+                    &non_zero.to_string().replace("NonZeroU", "u"),
+                    non_zero.span(),
+                );
+                quote!(::core::num::#non_zero::new_unchecked((#raw) as ::core::primitive::#primitive))
+            }
+            Some(CoreType::Option(inner)) => {
+                let presence_bit = quote! {
+                    (1 << (<#inner as #compatible>::SHIFT + <#inner as #compatible>::BITS_LEN))
+                };
+                let some = from_raw(inner, quote!(__raw));
+                let some_without_presence_bit = from_raw(inner, quote!(__raw & !#presence_bit));
+                quote! {
+                    {
+                        let __raw: ::core::primitive::u128 = #raw;
+                        match <#inner as #compatible>::NICHE {
+                            ::superbitty::__helpers::Some(__niche) if __raw == __niche => {
+                                ::superbitty::__helpers::None
+                            }
+                            ::superbitty::__helpers::Some(_) => ::superbitty::__helpers::Some(#some),
+                            ::superbitty::__helpers::None if __raw & #presence_bit == 0 => {
+                                ::superbitty::__helpers::None
+                            }
+                            ::superbitty::__helpers::None => {
+                                ::superbitty::__helpers::Some(#some_without_presence_bit)
+                            }
+                        }
+                    }
+                }
+            }
+            None => quote_spanned!(ty.span()=> <#ty>::__superbitty_from_raw(#raw)),
+        },
+    }
+}
+
+enum CoreType<'a> {
+    /// `bool`, `char` and `u8` to `u64`.
+    Primitive(&'a syn::Ident),
+    /// `NonZeroU8` to `NonZeroU64`.
+    NonZero(&'a syn::Ident),
+    Option(&'a syn::Type),
+}
+
+/// Recognizes the types from `core` by their name. The generated code ascribes the real types,
+/// so shadowing them causes a type error instead of a wrong conversion.
+fn core_type(ty: &syn::Type) -> Option<CoreType<'_>> {
+    let syn::Type::Path(syn::TypePath { qself: None, path }) = ty else { return None };
+    let last = path.segments.last()?;
+    let name = last.ident.to_string();
+    match &last.arguments {
+        syn::PathArguments::None => {
+            if ["bool", "char", "u8", "u16", "u32", "u64"].contains(&&*name) {
+                Some(CoreType::Primitive(&last.ident))
+            } else if ["NonZeroU8", "NonZeroU16", "NonZeroU32", "NonZeroU64"].contains(&&*name) {
+                Some(CoreType::NonZero(&last.ident))
+            } else {
+                None
+            }
+        }
+        syn::PathArguments::AngleBracketed(arguments) if name == "Option" => {
+            match arguments.args.iter().collect::<Vec<_>>()[..] {
+                [syn::GenericArgument::Type(inner)] => Some(CoreType::Option(inner)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
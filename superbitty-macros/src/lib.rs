@@ -2,6 +2,7 @@
 
 mod bit_field_compatible;
 mod bitfields;
+mod const_raw;
 mod utils;
 
 use proc_macro::TokenStream;
@@ -0,0 +1,475 @@
+use core::fmt;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::BitFieldCompatible;
+
+/// How to round a number that cannot be represented exactly by a fixed-point type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    /// Round to the nearest representable value, and away from zero on ties.
+    Nearest,
+    /// Round toward negative infinity.
+    Floor,
+    /// Round toward positive infinity.
+    Ceil,
+    /// Round toward zero.
+    TowardZero,
+}
+
+/// An unsigned fixed-point number with `INT` integer bits and `FRAC` fractional bits,
+/// that is, a `UQ<INT>.<FRAC>` number. `INT + FRAC` must be between 1 and 127 (inclusive).
+///
+/// As a bitfield, it takes `INT + FRAC` bits.
+///
+/// Arithmetic operators panic on overflow (and on division by zero). Multiplication and
+/// division truncate the result toward zero.
+///
+/// ```
+/// # use superbitty::{bitfields, Rounding, UFixed};
+/// bitfields! {
+///     pub struct Config : u16 {
+///         pub ratio: UFixed<2, 6>,
+///         pub enabled: bool,
+///     }
+/// }
+///
+/// let ratio = UFixed::<2, 6>::from_f64(1.5, Rounding::Nearest).unwrap();
+/// let config = Config::new(ratio, true);
+/// assert_eq!(config.ratio().to_f64(), 1.5);
+/// assert_eq!(config.0.raw(), 0b1_01_100000);
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UFixed<const INT: u32, const FRAC: u32>(u128);
+
+/// A signed fixed-point number with `INT` integer bits (including the sign bit) and `FRAC`
+/// fractional bits, that is, a `Q<INT>.<FRAC>` number. `INT + FRAC` must be between 1 and
+/// 127 (inclusive).
+///
+/// As a bitfield, it is stored in two's complement in `INT + FRAC` bits, and sign-extended
+/// when read back.
+///
+/// Arithmetic operators panic on overflow (and on division by zero). Multiplication and
+/// division truncate the result toward zero.
+///
+/// ```
+/// # use superbitty::{bitfields, Fixed, Rounding};
+/// bitfields! {
+///     pub struct Amplifier : u16 {
+///         pub gain: Fixed<4, 8>,
+///     }
+/// }
+///
+/// let gain = Fixed::<4, 8>::from_f64(-2.25, Rounding::Nearest).unwrap();
+/// let amplifier = Amplifier::new(gain);
+/// assert_eq!(amplifier.gain().to_f64(), -2.25);
+/// assert_eq!(amplifier.0.raw(), 0b1101_1100_0000);
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed<const INT: u32, const FRAC: u32>(i128);
+
+impl<const INT: u32, const FRAC: u32> UFixed<INT, FRAC> {
+    /// The smallest value that can be represented by this type (zero).
+    pub const MIN: Self = Self(0);
+
+    /// The largest value that can be represented by this type (`2^INT - 2^-FRAC`).
+    pub const MAX: Self = Self(u128::MAX >> (u128::BITS - Self::BITS));
+
+    /// The sign and magnitude of the raw bits.
+    #[inline]
+    const fn to_sign_magnitude(self) -> (bool, u128) {
+        (false, self.0)
+    }
+
+    /// The inverse of [`to_sign_magnitude()`](Self::to_sign_magnitude). Unsigned numbers
+    /// are never negative, so the sign is ignored.
+    #[inline]
+    const fn from_sign_magnitude(_negative: bool, magnitude: u128) -> Option<Self> {
+        Self::from_bits(magnitude)
+    }
+}
+
+impl<const INT: u32, const FRAC: u32> Fixed<INT, FRAC> {
+    /// The smallest value that can be represented by this type (`-2^(INT - 1)`).
+    pub const MIN: Self = Self(i128::MIN >> (i128::BITS - Self::BITS));
+
+    /// The largest value that can be represented by this type (`2^(INT - 1) - 2^-FRAC`).
+    pub const MAX: Self = Self(i128::MAX >> (i128::BITS - Self::BITS));
+
+    /// Checked negation. Returns `None` if `self` is [`MIN`](Self::MIN).
+    #[inline]
+    pub const fn checked_neg(self) -> Option<Self> {
+        Self::from_bits(-self.0)
+    }
+
+    /// The sign and magnitude of the raw bits.
+    #[inline]
+    const fn to_sign_magnitude(self) -> (bool, u128) {
+        (self.0 < 0, self.0.unsigned_abs())
+    }
+
+    /// The inverse of [`to_sign_magnitude()`](Self::to_sign_magnitude), or `None` if the
+    /// result is out of range.
+    #[inline]
+    const fn from_sign_magnitude(negative: bool, magnitude: u128) -> Option<Self> {
+        // `i128::MIN` is never in range, so we don't need to special-case it.
+        if magnitude > i128::MAX as u128 {
+            return None;
+        }
+        match negative {
+            true => Self::from_bits(-(magnitude as i128)),
+            false => Self::from_bits(magnitude as i128),
+        }
+    }
+}
+
+/// Rounds `scaled` to an integer, or returns `None` if it is not finite or too big.
+fn round(scaled: f64, rounding: Rounding) -> Option<i128> {
+    // 2^127, exactly.
+    const LIMIT: f64 = i128::MAX as f64;
+    if !(-LIMIT < scaled && scaled < LIMIT) {
+        return None;
+    }
+    let truncated = scaled as i128;
+    // Exact, since big floats have no fractional part.
+    let fraction = scaled - truncated as f64;
+    let rounded = match rounding {
+        Rounding::Nearest if fraction >= 0.5 => truncated + 1,
+        Rounding::Nearest if fraction <= -0.5 => truncated - 1,
+        Rounding::Floor if fraction < 0.0 => truncated - 1,
+        Rounding::Ceil if fraction > 0.0 => truncated + 1,
+        _ => truncated,
+    };
+    Some(rounded)
+}
+
+/// The full 256-bit product of `a` and `b`, as `(high, low)`.
+const fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const LOW_HALF: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW_HALF);
+    let (b_high, b_low) = (b >> 64, b & LOW_HALF);
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    // At most three 64-bit values, so it cannot overflow.
+    let middle = (low_low >> 64) + (high_low & LOW_HALF) + (low_high & LOW_HALF);
+    let low = (middle << 64) | (low_low & LOW_HALF);
+    let high = a_high * b_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// Shifts the 256-bit `(high, low)` right by `shift` (less than 128) bits, or returns
+/// `None` if the result does not fit in 128 bits.
+const fn wide_shr(high: u128, low: u128, shift: u32) -> Option<u128> {
+    if shift == 0 {
+        return if high == 0 { Some(low) } else { None };
+    }
+    if high >> shift != 0 {
+        return None;
+    }
+    Some((low >> shift) | (high << (u128::BITS - shift)))
+}
+
+/// Divides the 256-bit `(high, low)` by `divisor`, truncating, or returns `None` if
+/// `divisor` is zero or the result does not fit in 128 bits.
+const fn wide_div(high: u128, low: u128, divisor: u128) -> Option<u128> {
+    if high == 0 {
+        return low.checked_div(divisor);
+    }
+    if high >= divisor {
+        return None;
+    }
+    // Long division, one bit at a time. The remainder is always less than `divisor`, but
+    // shifting it can overflow, in which case it is certainly bigger than `divisor`.
+    let mut remainder = high;
+    let mut quotient = 0;
+    let mut bit = u128::BITS;
+    while bit > 0 {
+        bit -= 1;
+        let overflow = remainder >> (u128::BITS - 1) != 0;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if overflow || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient |= 1;
+        }
+    }
+    Some(quotient)
+}
+
+macro_rules! fixed_impls {
+    ( $( $ty:ident($repr:ident) )* ) => {
+        $(
+            impl<const INT: u32, const FRAC: u32> $ty<INT, FRAC> {
+                /// The size of this type in bits (`INT + FRAC`).
+                pub const BITS: u32 = {
+                    assert!(
+                        INT + FRAC >= 1 && INT + FRAC <= 127,
+                        "fixed-point types must have between 1 and 127 bits",
+                    );
+                    INT + FRAC
+                };
+
+                /// The smallest positive value that can be represented by this type
+                /// (`2^-FRAC`).
+                pub const DELTA: Self = Self(1);
+
+                /// Creates a number from its raw bits (the value multiplied by `2^FRAC`),
+                /// or returns `None` if they do not fit in `INT + FRAC` bits.
+                #[inline]
+                pub const fn from_bits(bits: $repr) -> Option<Self> {
+                    if Self::MIN.0 <= bits && bits <= Self::MAX.0 {
+                        Some(Self(bits))
+                    } else {
+                        None
+                    }
+                }
+
+                /// The raw bits of this number (the value multiplied by `2^FRAC`).
+                #[inline]
+                pub const fn to_bits(self) -> $repr {
+                    self.0
+                }
+
+                /// Converts an `f64` to this type, rounding it as requested. Returns `None`
+                /// if the value is NaN or out of range.
+                pub fn from_f64(value: f64, rounding: Rounding) -> Option<Self> {
+                    let bits = round(value * Self::scale(), rounding)?;
+                    Self::from_bits(<$repr>::try_from(bits).ok()?)
+                }
+
+                /// Converts an `f32` to this type, rounding it as requested. Returns `None`
+                /// if the value is NaN or out of range.
+                #[inline]
+                pub fn from_f32(value: f32, rounding: Rounding) -> Option<Self> {
+                    // Converting to `f64` is exact.
+                    Self::from_f64(value.into(), rounding)
+                }
+
+                /// Converts this number to an `f64`, rounding to the nearest `f64` if it
+                /// cannot be represented exactly.
+                #[inline]
+                pub fn to_f64(self) -> f64 {
+                    self.0 as f64 / Self::scale()
+                }
+
+                /// Converts this number to an `f32`, rounding to the nearest `f32` if it
+                /// cannot be represented exactly.
+                #[inline]
+                pub fn to_f32(self) -> f32 {
+                    // Going through `f64` would round twice. Dividing by a power of two is
+                    // exact even for the smallest values, so this only rounds once.
+                    self.0 as f32 / (1u128 << FRAC) as f32
+                }
+
+                /// `2^FRAC`.
+                #[inline]
+                fn scale() -> f64 {
+                    (1u128 << FRAC) as f64
+                }
+
+                /// Checked addition. Returns `None` on overflow.
+                #[inline]
+                pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+                    match self.0.checked_add(rhs.0) {
+                        Some(result) => Self::from_bits(result),
+                        None => None,
+                    }
+                }
+
+                /// Checked subtraction. Returns `None` on overflow.
+                #[inline]
+                pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    match self.0.checked_sub(rhs.0) {
+                        Some(result) => Self::from_bits(result),
+                        None => None,
+                    }
+                }
+
+                /// Checked multiplication, truncating toward zero. Returns `None` on overflow.
+                #[inline]
+                pub const fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    let (lhs_negative, lhs) = self.to_sign_magnitude();
+                    let (rhs_negative, rhs) = rhs.to_sign_magnitude();
+                    // The product can take up to 254 bits before scaling it back. Truncating
+                    // the magnitude truncates toward zero.
+                    let (high, low) = widening_mul(lhs, rhs);
+                    match wide_shr(high, low, FRAC) {
+                        Some(result) => Self::from_sign_magnitude(lhs_negative != rhs_negative, result),
+                        None => None,
+                    }
+                }
+
+                /// Checked division, truncating toward zero. Returns `None` if `rhs` is zero
+                /// or on overflow.
+                #[inline]
+                pub const fn checked_div(self, rhs: Self) -> Option<Self> {
+                    let (lhs_negative, lhs) = self.to_sign_magnitude();
+                    let (rhs_negative, rhs) = rhs.to_sign_magnitude();
+                    // The dividend is scaled up by `FRAC` bits, which may not fit in 128 bits.
+                    let (high, low) = match FRAC {
+                        0 => (0, lhs),
+                        _ => (lhs >> (u128::BITS - FRAC), lhs << FRAC),
+                    };
+                    match wide_div(high, low, rhs) {
+                        Some(result) => Self::from_sign_magnitude(lhs_negative != rhs_negative, result),
+                        None => None,
+                    }
+                }
+
+                /// Saturating addition.
+                #[inline]
+                pub const fn saturating_add(self, rhs: Self) -> Self {
+                    match self.checked_add(rhs) {
+                        Some(result) => result,
+                        None if rhs.0 > 0 => Self::MAX,
+                        None => Self::MIN,
+                    }
+                }
+
+                /// Saturating subtraction.
+                #[inline]
+                pub const fn saturating_sub(self, rhs: Self) -> Self {
+                    match self.checked_sub(rhs) {
+                        Some(result) => result,
+                        None if rhs.0 > 0 => Self::MIN,
+                        None => Self::MAX,
+                    }
+                }
+            }
+
+            fixed_impls!(@op $ty; Add::add, AddAssign::add_assign => checked_add, "attempt to add with overflow");
+            fixed_impls!(@op $ty; Sub::sub, SubAssign::sub_assign => checked_sub, "attempt to subtract with overflow");
+            fixed_impls!(@op $ty; Mul::mul, MulAssign::mul_assign => checked_mul, "attempt to multiply with overflow");
+            fixed_impls!(
+                @op $ty; Div::div, DivAssign::div_assign => checked_div,
+                "attempt to divide by zero or with overflow"
+            );
+
+            impl<const INT: u32, const FRAC: u32> fmt::Debug for $ty<INT, FRAC> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    fmt::Debug::fmt(&self.to_f64(), f)
+                }
+            }
+
+            impl<const INT: u32, const FRAC: u32> fmt::Display for $ty<INT, FRAC> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    fmt::Display::fmt(&self.to_f64(), f)
+                }
+            }
+        )*
+    };
+    ( @op $ty:ident; $trait:ident::$method:ident, $assign_trait:ident::$assign_method:ident
+        => $checked:ident, $message:literal ) => {
+        impl<const INT: u32, const FRAC: u32> $trait for $ty<INT, FRAC> {
+            type Output = Self;
+
+            #[inline]
+            #[track_caller]
+            fn $method(self, rhs: Self) -> Self {
+                match self.$checked(rhs) {
+                    Some(result) => result,
+                    None => panic!($message),
+                }
+            }
+        }
+
+        impl<const INT: u32, const FRAC: u32> $assign_trait for $ty<INT, FRAC> {
+            #[inline]
+            #[track_caller]
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = $trait::$method(*self, rhs);
+            }
+        }
+    };
+}
+
+fixed_impls!(UFixed(u128) Fixed(i128));
+
+impl<const INT: u32, const FRAC: u32> Neg for Fixed<INT, FRAC> {
+    type Output = Self;
+
+    #[inline]
+    #[track_caller]
+    fn neg(self) -> Self {
+        match self.checked_neg() {
+            Some(result) => result,
+            None => panic!("attempt to negate with overflow"),
+        }
+    }
+}
+
+// Const versions of the conversions, for `#[bitfields(const)]` structs.
+impl<const INT: u32, const FRAC: u32> UFixed<INT, FRAC> {
+    #[doc(hidden)]
+    #[inline]
+    pub const fn __superbitty_into_raw(self) -> u128 {
+        self.0
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub const unsafe fn __superbitty_from_raw(v: u128) -> Self {
+        Self(v)
+    }
+}
+
+// SAFETY: The value always fits in `BITS` bits.
+unsafe impl<const INT: u32, const FRAC: u32> BitFieldCompatible for UFixed<INT, FRAC> {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = Self::BITS;
+
+    #[inline]
+    fn into_raw(self) -> u128 {
+        Self::__superbitty_into_raw(self)
+    }
+
+    #[inline]
+    unsafe fn from_raw(v: u128) -> Self {
+        // SAFETY: Guaranteed by the caller.
+        unsafe { Self::__superbitty_from_raw(v) }
+    }
+
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        v <= Self::MAX.0
+    }
+}
+
+// Const versions of the conversions, for `#[bitfields(const)]` structs.
+impl<const INT: u32, const FRAC: u32> Fixed<INT, FRAC> {
+    #[doc(hidden)]
+    #[inline]
+    pub const fn __superbitty_into_raw(self) -> u128 {
+        self.0 as u128 & <Self as BitFieldCompatible>::BITS_MASK
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub const unsafe fn __superbitty_from_raw(v: u128) -> Self {
+        // Sign-extend.
+        let unused_bits = i128::BITS - Self::BITS;
+        Self(((v as i128) << unused_bits) >> unused_bits)
+    }
+}
+
+// SAFETY: We mask the value to `BITS` bits.
+unsafe impl<const INT: u32, const FRAC: u32> BitFieldCompatible for Fixed<INT, FRAC> {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = Self::BITS;
+
+    #[inline]
+    fn into_raw(self) -> u128 {
+        Self::__superbitty_into_raw(self)
+    }
+
+    #[inline]
+    unsafe fn from_raw(v: u128) -> Self {
+        // SAFETY: Guaranteed by the caller.
+        unsafe { Self::__superbitty_from_raw(v) }
+    }
+
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        v <= Self::BITS_MASK
+    }
+}
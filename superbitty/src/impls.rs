@@ -0,0 +1,288 @@
+//! [`BitFieldCompatible`] implementations for types from `core`.
+
+use core::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+
+use crate::raw::low_bits_mask;
+use crate::BitFieldCompatible;
+
+// SAFETY: `into_raw()` returns either 0 or 1, which fits in one bit.
+unsafe impl BitFieldCompatible for bool {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = 1;
+
+    #[inline]
+    fn into_raw(self) -> u128 {
+        self as u128
+    }
+
+    #[inline]
+    unsafe fn from_raw(v: u128) -> Self {
+        v != 0
+    }
+
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        v <= 1
+    }
+}
+
+macro_rules! primitive_impls {
+    ( $( $primitive:ident $non_zero:ident ),* ) => {
+        $(
+            // SAFETY: The integer takes its full width.
+            unsafe impl BitFieldCompatible for $primitive {
+                const SHIFT: u32 = 0;
+                const BITS_LEN: u32 = $primitive::BITS;
+
+                #[inline]
+                fn into_raw(self) -> u128 {
+                    self as u128
+                }
+
+                #[inline]
+                unsafe fn from_raw(v: u128) -> Self {
+                    v as $primitive
+                }
+
+                #[inline]
+                fn is_valid_raw(v: u128) -> bool {
+                    v <= $primitive::MAX as u128
+                }
+            }
+
+            // SAFETY: Like the primitive integer, except zero, which is never used.
+            unsafe impl BitFieldCompatible for $non_zero {
+                const SHIFT: u32 = 0;
+                const BITS_LEN: u32 = $primitive::BITS;
+                const NICHE: Option<u128> = Some(0);
+
+                #[inline]
+                fn into_raw(self) -> u128 {
+                    self.get() as u128
+                }
+
+                #[inline]
+                unsafe fn from_raw(v: u128) -> Self {
+                    // SAFETY: The caller guarantees `v` is valid, i.e. not zero.
+                    unsafe { $non_zero::new_unchecked(v as $primitive) }
+                }
+
+                #[inline]
+                fn is_valid_raw(v: u128) -> bool {
+                    v != 0 && v <= $primitive::MAX as u128
+                }
+            }
+        )*
+    };
+}
+
+primitive_impls!(u8 NonZeroU8, u16 NonZeroU16, u32 NonZeroU32, u64 NonZeroU64);
+
+// SAFETY: The largest `char` is `0x10FFFF`, which fits in 21 bits.
+unsafe impl BitFieldCompatible for char {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = 21;
+    // The first surrogate, which is not a valid `char`.
+    const NICHE: Option<u128> = Some(0xD800);
+
+    #[inline]
+    fn into_raw(self) -> u128 {
+        self as u128
+    }
+
+    #[inline]
+    unsafe fn from_raw(v: u128) -> Self {
+        // SAFETY: The caller guarantees `v` is a valid `char`.
+        unsafe { char::from_u32_unchecked(v as u32) }
+    }
+
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        u32::try_from(v).is_ok_and(|v| char::from_u32(v).is_some())
+    }
+}
+
+// SAFETY: With a niche, `None` is stored as the niche, which `T` never uses. Without
+// one, `Some` is stored with an extra bit set after the bits of `T`, and `None` as zero.
+unsafe impl<T: BitFieldCompatible> BitFieldCompatible for Option<T> {
+    const SHIFT: u32 = T::SHIFT;
+    const BITS_LEN: u32 = match T::NICHE {
+        Some(_) => T::BITS_LEN,
+        None if T::SHIFT + T::BITS_LEN >= u128::BITS => {
+            panic!("`Option<T>` needs a presence bit, but `T` has no room left for it")
+        }
+        None => T::BITS_LEN + 1,
+    };
+
+    // Without a niche in `T`, any non-zero value without the presence bit is unused.
+    const NICHE: Option<u128> = match T::NICHE {
+        Some(_) => None,
+        None if T::BITS_LEN > 0 => Some(1 << T::SHIFT),
+        None => None,
+    };
+
+    #[inline]
+    fn into_raw(self) -> u128 {
+        match (self, T::NICHE) {
+            (Some(v), Some(_)) => v.into_raw(),
+            (None, Some(niche)) => niche,
+            (Some(v), None) => v.into_raw() | presence_bit::<T>(),
+            (None, None) => 0,
+        }
+    }
+
+    #[inline]
+    unsafe fn from_raw(v: u128) -> Self {
+        match T::NICHE {
+            Some(niche) if v == niche => None,
+            // SAFETY: Guaranteed by the caller.
+            Some(_) => Some(unsafe { T::from_raw(v) }),
+            None if v & presence_bit::<T>() == 0 => None,
+            // SAFETY: Guaranteed by the caller.
+            None => Some(unsafe { T::from_raw(v & !presence_bit::<T>()) }),
+        }
+    }
+
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        match T::NICHE {
+            Some(niche) => v == niche || T::is_valid_raw(v),
+            None if v & presence_bit::<T>() == 0 => v == 0,
+            None => T::is_valid_raw(v & !presence_bit::<T>()),
+        }
+    }
+}
+
+/// The bit that marks `Some` in an `Option<T>`, if `T` has no niche.
+const fn presence_bit<T: BitFieldCompatible>() -> u128 {
+    1 << (T::SHIFT + T::BITS_LEN)
+}
+
+// SAFETY: The elements are packed one after the other, each taking `T::BITS_LEN` bits,
+// so the array takes `N * T::BITS_LEN` bits.
+unsafe impl<T: BitFieldCompatible, const N: usize> BitFieldCompatible for [T; N] {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = T::BITS_LEN * N as u32;
+
+    // The niche of the first element, with all other elements zeroed.
+    const NICHE: Option<u128> = match T::NICHE {
+        Some(niche) if N > 0 => Some(niche >> T::SHIFT),
+        _ => None,
+    };
+
+    #[inline]
+    fn into_raw(self) -> u128 {
+        let mut raw = 0;
+        for (index, element) in self.into_iter().enumerate() {
+            raw |= (element.into_raw() >> T::SHIFT) << (index as u32 * T::BITS_LEN);
+        }
+        raw
+    }
+
+    #[inline]
+    unsafe fn from_raw(v: u128) -> Self {
+        core::array::from_fn(|index| {
+            // SAFETY: Guaranteed by the caller.
+            unsafe { T::from_raw(field_raw::<T>(v, index as u32 * T::BITS_LEN)) }
+        })
+    }
+
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        v & !low_bits_mask(Self::BITS_LEN) == 0
+            && (0..N).all(|index| T::is_valid_raw(field_raw::<T>(v, index as u32 * T::BITS_LEN)))
+    }
+}
+
+macro_rules! tuple_impls {
+    ( $( ( $( $ty:ident $index:tt ),+ ) )+ ) => {
+        $(
+            // SAFETY: The elements are packed one after the other, each taking its
+            // `BITS_LEN` bits, so the tuple takes the sum of them.
+            unsafe impl<$($ty: BitFieldCompatible),+> BitFieldCompatible for ($($ty,)+) {
+                const SHIFT: u32 = 0;
+                const BITS_LEN: u32 = 0 $( + $ty::BITS_LEN )+;
+
+                // The first niche of an element, with all other elements zeroed.
+                #[allow(unused_assignments)]
+                const NICHE: Option<u128> = {
+                    let mut niche = None;
+                    let mut offset = 0;
+                    $(
+                        if let (None, Some(element_niche)) = (niche, $ty::NICHE) {
+                            niche = Some((element_niche >> $ty::SHIFT) << offset);
+                        }
+                        offset += $ty::BITS_LEN;
+                    )+
+                    niche
+                };
+
+                #[inline]
+                #[allow(unused_assignments)]
+                fn into_raw(self) -> u128 {
+                    let mut raw = 0;
+                    let mut offset = 0;
+                    $(
+                        // Zero-width elements may start right after the 128th bit.
+                        raw |= (self.$index.into_raw() >> $ty::SHIFT).checked_shl(offset).unwrap_or(0);
+                        offset += $ty::BITS_LEN;
+                    )+
+                    raw
+                }
+
+                #[inline]
+                #[allow(unused_assignments)]
+                unsafe fn from_raw(v: u128) -> Self {
+                    let mut offset = 0;
+                    ($(
+                        {
+                            // SAFETY: Guaranteed by the caller.
+                            let element = unsafe { $ty::from_raw(field_raw::<$ty>(v, offset)) };
+                            offset += $ty::BITS_LEN;
+                            element
+                        },
+                    )+)
+                }
+
+                #[inline]
+                #[allow(unused_assignments)]
+                fn is_valid_raw(v: u128) -> bool {
+                    if v & !low_bits_mask(Self::BITS_LEN) != 0 {
+                        return false;
+                    }
+                    let mut offset = 0;
+                    $(
+                        if !$ty::is_valid_raw(field_raw::<$ty>(v, offset)) {
+                            return false;
+                        }
+                        offset += $ty::BITS_LEN;
+                    )+
+                    true
+                }
+            }
+        )+
+    };
+}
+
+tuple_impls! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+/// Extracts the raw value of a packed `T` (whose trailing zeros are trimmed) that starts
+/// at `offset`.
+#[inline]
+fn field_raw<T: BitFieldCompatible>(v: u128, offset: u32) -> u128 {
+    // Zero-width elements may start right after the 128th bit.
+    (v.checked_shr(offset).unwrap_or(0) & low_bits_mask(T::BITS_LEN)) << T::SHIFT
+}
@@ -0,0 +1,339 @@
+use core::fmt;
+use core::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign,
+    Mul, MulAssign, Neg, Not, Rem, RemAssign, Sub, SubAssign,
+};
+
+use crate::uint::{aliases, arith_ops, bit_ops, fmt_impls, OutOfRangeError};
+use crate::BitFieldCompatible;
+
+/// A signed integer with `BITS` bits, where `BITS` is between 1 and 127 (inclusive).
+///
+/// There are also type aliases for all widths, from [`i1`] to [`i127`] (except those of
+/// the primitive types).
+///
+/// As a bitfield, it is stored in two's complement in `BITS` bits, and sign-extended
+/// when read back.
+///
+/// Arithmetic operators behave like those of the primitive integers: they panic on overflow
+/// when debug assertions are enabled, and wrap around otherwise.
+///
+/// ```
+/// # use superbitty::{bitfields, i4, u4, BitFieldCompatible};
+/// bitfields! {
+///     pub struct Sensor : u8 {
+///         pub offset: i4,
+///         pub gain: u4,
+///     }
+/// }
+///
+/// let mut sensor = Sensor::new(i4::new(-3).unwrap(), u4::new(2).unwrap());
+/// assert_eq!(sensor.offset().value(), -3);
+/// assert_eq!(sensor.0.raw(), 0b0010_1101);
+///
+/// sensor.set_offset(i4::MIN);
+/// assert_eq!(sensor.offset().value(), -8);
+/// assert_eq!(sensor.gain().value(), 2);
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Int<const BITS: u32>(i128);
+
+impl<const BITS: u32> Int<BITS> {
+    /// The size of this integer type in bits.
+    pub const BITS: u32 = {
+        assert!(BITS >= 1 && BITS <= 127, "`Int` must have between 1 and 127 bits");
+        BITS
+    };
+
+    /// The smallest value that can be represented by this integer type (`-2^(BITS - 1)`).
+    pub const MIN: Self = Self(i128::MIN >> (i128::BITS - Self::BITS));
+
+    /// The largest value that can be represented by this integer type (`2^(BITS - 1) - 1`).
+    pub const MAX: Self = Self(i128::MAX >> (i128::BITS - Self::BITS));
+
+    /// Creates a new integer, or returns `None` if `value` does not fit in `BITS` bits.
+    #[inline]
+    pub const fn new(value: i128) -> Option<Self> {
+        if Self::MIN.0 <= value && value <= Self::MAX.0 {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a new integer, truncating `value` to its low `BITS` bits and
+    /// sign-extending them.
+    #[inline]
+    pub const fn new_wrapping(value: i128) -> Self {
+        let unused_bits = i128::BITS - Self::BITS;
+        Self((value << unused_bits) >> unused_bits)
+    }
+
+    /// Creates a new integer, clamping `value` to the range from [`MIN`](Self::MIN)
+    /// to [`MAX`](Self::MAX).
+    #[inline]
+    pub const fn new_saturating(value: i128) -> Self {
+        if value < Self::MIN.0 {
+            Self::MIN
+        } else if value > Self::MAX.0 {
+            Self::MAX
+        } else {
+            Self(value)
+        }
+    }
+
+    /// Creates a new integer without checking that `value` fits in `BITS` bits.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be between [`MIN`](Self::MIN) and [`MAX`](Self::MAX) (inclusive).
+    #[inline]
+    pub const unsafe fn new_unchecked(value: i128) -> Self {
+        Self(value)
+    }
+
+    /// The value of this integer.
+    #[inline]
+    pub const fn value(self) -> i128 {
+        self.0
+    }
+
+    /// Checked addition. Returns `None` on overflow.
+    #[inline]
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        // Both are within the range of `i127`, so this cannot overflow `i128`.
+        Self::new(self.0 + rhs.0)
+    }
+
+    /// Checked subtraction. Returns `None` on overflow.
+    #[inline]
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Self::new(self.0 - rhs.0)
+    }
+
+    /// Checked multiplication. Returns `None` on overflow.
+    #[inline]
+    pub const fn checked_mul(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_mul(rhs.0) {
+            Some(result) => Self::new(result),
+            None => None,
+        }
+    }
+
+    /// Checked division. Returns `None` if `rhs` is zero or the division overflows.
+    #[inline]
+    pub const fn checked_div(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_div(rhs.0) {
+            Some(result) => Self::new(result),
+            None => None,
+        }
+    }
+
+    /// Checked remainder. Returns `None` if `rhs` is zero.
+    #[inline]
+    pub const fn checked_rem(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_rem(rhs.0) {
+            Some(result) => Some(Self(result)),
+            None => None,
+        }
+    }
+
+    /// Checked negation. Returns `None` if `self` is [`MIN`](Self::MIN).
+    #[inline]
+    pub const fn checked_neg(self) -> Option<Self> {
+        Self::new(-self.0)
+    }
+
+    /// Wrapping (modular) addition.
+    #[inline]
+    pub const fn wrapping_add(self, rhs: Self) -> Self {
+        Self::new_wrapping(self.0 + rhs.0)
+    }
+
+    /// Wrapping (modular) subtraction.
+    #[inline]
+    pub const fn wrapping_sub(self, rhs: Self) -> Self {
+        Self::new_wrapping(self.0 - rhs.0)
+    }
+
+    /// Wrapping (modular) multiplication.
+    #[inline]
+    pub const fn wrapping_mul(self, rhs: Self) -> Self {
+        Self::new_wrapping(self.0.wrapping_mul(rhs.0))
+    }
+
+    /// Wrapping (modular) negation.
+    #[inline]
+    pub const fn wrapping_neg(self) -> Self {
+        Self::new_wrapping(-self.0)
+    }
+
+    /// Saturating addition.
+    #[inline]
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self::new_saturating(self.0 + rhs.0)
+    }
+
+    /// Saturating subtraction.
+    #[inline]
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self::new_saturating(self.0 - rhs.0)
+    }
+
+    /// Saturating multiplication.
+    #[inline]
+    pub const fn saturating_mul(self, rhs: Self) -> Self {
+        Self::new_saturating(self.0.saturating_mul(rhs.0))
+    }
+}
+
+// Const versions of the conversions, for `#[bitfields(const)]` structs.
+impl<const BITS: u32> Int<BITS> {
+    #[doc(hidden)]
+    #[inline]
+    pub const fn __superbitty_into_raw(self) -> u128 {
+        self.0 as u128 & <Self as BitFieldCompatible>::BITS_MASK
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub const unsafe fn __superbitty_from_raw(v: u128) -> Self {
+        Self::new_wrapping(v as i128)
+    }
+}
+
+// SAFETY: We mask the value to `BITS` bits.
+unsafe impl<const BITS: u32> BitFieldCompatible for Int<BITS> {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = Self::BITS;
+
+    #[inline]
+    fn into_raw(self) -> u128 {
+        Self::__superbitty_into_raw(self)
+    }
+
+    #[inline]
+    unsafe fn from_raw(v: u128) -> Self {
+        // SAFETY: Guaranteed by the caller.
+        unsafe { Self::__superbitty_from_raw(v) }
+    }
+
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        v <= Self::BITS_MASK
+    }
+}
+
+arith_ops! {
+    Int;
+    Add::add, AddAssign::add_assign
+        => checked_add, Self::wrapping_add, "attempt to add with overflow";
+    Sub::sub, SubAssign::sub_assign
+        => checked_sub, Self::wrapping_sub, "attempt to subtract with overflow";
+    Mul::mul, MulAssign::mul_assign
+        => checked_mul, Self::wrapping_mul, "attempt to multiply with overflow";
+    Div::div, DivAssign::div_assign
+        => checked_div, |a: Self, b: Self| Self::new_wrapping(a.0 / b.0),
+        "attempt to divide with overflow";
+    Rem::rem, RemAssign::rem_assign
+        => checked_rem, |a: Self, b: Self| Self(a.0 % b.0),
+        "attempt to calculate the remainder with a divisor of zero";
+}
+
+bit_ops! {
+    Int;
+    BitAnd::bitand, BitAndAssign::bitand_assign => &;
+    BitOr::bitor, BitOrAssign::bitor_assign => |;
+    BitXor::bitxor, BitXorAssign::bitxor_assign => ^;
+}
+
+impl<const BITS: u32> Not for Int<BITS> {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self {
+        // `!x == -x - 1`, which is always in range.
+        Self(!self.0)
+    }
+}
+
+impl<const BITS: u32> Neg for Int<BITS> {
+    type Output = Self;
+
+    #[inline]
+    #[track_caller]
+    fn neg(self) -> Self {
+        if cfg!(debug_assertions) {
+            match self.checked_neg() {
+                Some(result) => result,
+                None => panic!("attempt to negate with overflow"),
+            }
+        } else {
+            self.wrapping_neg()
+        }
+    }
+}
+
+fmt_impls!(Int; Debug Display);
+
+macro_rules! primitive_conversions {
+    ( $($primitive:ident)* ) => {
+        $(
+            impl<const BITS: u32> TryFrom<$primitive> for Int<BITS> {
+                type Error = OutOfRangeError;
+
+                #[inline]
+                fn try_from(value: $primitive) -> Result<Self, OutOfRangeError> {
+                    Self::new(value as i128).ok_or(OutOfRangeError(()))
+                }
+            }
+
+            impl<const BITS: u32> TryFrom<Int<BITS>> for $primitive {
+                type Error = OutOfRangeError;
+
+                #[inline]
+                fn try_from(value: Int<BITS>) -> Result<Self, OutOfRangeError> {
+                    $primitive::try_from(value.0).map_err(|_| OutOfRangeError(()))
+                }
+            }
+        )*
+    };
+}
+
+primitive_conversions!(i8 i16 i32 i64 isize);
+
+impl<const BITS: u32> TryFrom<i128> for Int<BITS> {
+    type Error = OutOfRangeError;
+
+    #[inline]
+    fn try_from(value: i128) -> Result<Self, OutOfRangeError> {
+        Self::new(value).ok_or(OutOfRangeError(()))
+    }
+}
+
+impl<const BITS: u32> From<Int<BITS>> for i128 {
+    #[inline]
+    fn from(value: Int<BITS>) -> Self {
+        value.0
+    }
+}
+
+aliases! {
+    Int, "signed";
+    i1 = 1 i2 = 2 i3 = 3 i4 = 4 i5 = 5 i6 = 6 i7 = 7
+    i9 = 9 i10 = 10 i11 = 11 i12 = 12 i13 = 13 i14 = 14 i15 = 15
+    i17 = 17 i18 = 18 i19 = 19 i20 = 20 i21 = 21 i22 = 22 i23 = 23 i24 = 24
+    i25 = 25 i26 = 26 i27 = 27 i28 = 28 i29 = 29 i30 = 30 i31 = 31
+    i33 = 33 i34 = 34 i35 = 35 i36 = 36 i37 = 37 i38 = 38 i39 = 39 i40 = 40
+    i41 = 41 i42 = 42 i43 = 43 i44 = 44 i45 = 45 i46 = 46 i47 = 47 i48 = 48
+    i49 = 49 i50 = 50 i51 = 51 i52 = 52 i53 = 53 i54 = 54 i55 = 55 i56 = 56
+    i57 = 57 i58 = 58 i59 = 59 i60 = 60 i61 = 61 i62 = 62 i63 = 63
+    i65 = 65 i66 = 66 i67 = 67 i68 = 68 i69 = 69 i70 = 70 i71 = 71 i72 = 72
+    i73 = 73 i74 = 74 i75 = 75 i76 = 76 i77 = 77 i78 = 78 i79 = 79 i80 = 80
+    i81 = 81 i82 = 82 i83 = 83 i84 = 84 i85 = 85 i86 = 86 i87 = 87 i88 = 88
+    i89 = 89 i90 = 90 i91 = 91 i92 = 92 i93 = 93 i94 = 94 i95 = 95 i96 = 96
+    i97 = 97 i98 = 98 i99 = 99 i100 = 100 i101 = 101 i102 = 102 i103 = 103 i104 = 104
+    i105 = 105 i106 = 106 i107 = 107 i108 = 108 i109 = 109 i110 = 110 i111 = 111 i112 = 112
+    i113 = 113 i114 = 114 i115 = 115 i116 = 116 i117 = 117 i118 = 118 i119 = 119 i120 = 120
+    i121 = 121 i122 = 122 i123 = 123 i124 = 124 i125 = 125 i126 = 126 i127 = 127
+}
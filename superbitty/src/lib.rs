@@ -11,17 +11,9 @@
 //!     D,
 //! }
 //!
-//! #[derive(Clone, Copy)]
-//! pub struct Rest(pub u8);
-//!
-//! // SAFETY: We only set this via `Bitfields`, and thus the values are guaranteed
-//! // to stay in range.
-//! unsafe impl BitFieldCompatible for Rest {
-//!     const SHIFT: u32 = 0;
-//!     const BITS_LEN: u32 = 6;
-//!     fn into_raw(self) -> u128 { self.0 as u128 }
-//!     unsafe fn from_raw(v: u128) -> Self { Self(v as u8) }
-//! }
+//! #[derive(BitFieldCompatible, Clone, Copy)]
+//! #[bit_field(bits = 6)]
+//! pub struct Rest(u8);
 //!
 //! bitfields! {
 //!     pub struct Bitfields : u8 {
@@ -31,10 +23,14 @@
 //! }
 //!
 //! fn main() {
-//!     let mut instance = Bitfields::new(Enum::B, Rest(0b010));
+//!     let mut instance = Bitfields::new(Enum::B, Rest::new(0b010).unwrap());
 //!     assert_eq!(instance.e(), Enum::B);
-//!     instance.set_r(Rest(0b101));
+//!     instance.set_r(Rest::new(0b101).unwrap());
 //!     assert_eq!(instance.r().0, 0b101);
+//!     assert!(Rest::new(0b1000000).is_none());
+//!
+//!     let decoded = Bitfields::from_raw(instance.0.raw()).unwrap();
+//!     assert_eq!(decoded.r().0, 0b101);
 //! }
 //! ```
 
@@ -42,7 +38,11 @@
 #![forbid(unsafe_op_in_unsafe_fn, rust_2018_idioms)]
 #![warn(missing_docs)]
 
+mod fixed;
+mod impls;
+mod int;
 mod raw;
+mod uint;
 
 /// A bitfield struct.
 ///
@@ -73,6 +73,51 @@ mod raw;
 /// assert_eq!(instance.enum_b(), EnumB::C);
 /// ```
 ///
+/// Every setter `set_field()` also has a consuming counterpart `with_field()`, which returns
+/// the modified value and can be chained:
+/// ```rust
+/// # use superbitty::{bitfields, BitFieldCompatible};
+/// # #[derive(BitFieldCompatible, Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// # enum EnumA { #[default] A, B }
+/// # #[derive(BitFieldCompatible, Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// # enum EnumB { #[default] A, B, C }
+/// # bitfields! {
+/// #     #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// #     pub struct Bitfields : u8 {
+/// #         enum_a: EnumA,
+/// #         pub enum_b: EnumB,
+/// #     }
+/// # }
+/// let instance = Bitfields::default().with_enum_a(EnumA::B).with_enum_b(EnumB::C);
+/// assert_eq!(instance, Bitfields::new(EnumA::B, EnumB::C));
+/// ```
+///
+/// With `#[bitfields(const)]`, the constructor, getters, setters and `with_field()` methods
+/// are `const fn`s, so they can be used to build `const`s and `static`s. Trait methods cannot
+/// be called there, so the fields are converted with const versions of [`into_raw()`] and
+/// [`from_raw()`], which exist for the types from `core` and from this crate, for types that
+/// `#[derive(BitFieldCompatible)]` and for other `bitfields!` structs, but not for types that
+/// implement [`BitFieldCompatible`] manually. The accessors of alternative views (see below)
+/// and `is_view_valid()` need [`is_valid_raw()`], so they are never `const fn`s.
+/// ```rust
+/// # use superbitty::{bitfields, BitFieldCompatible, u4};
+/// #[derive(BitFieldCompatible, Clone, Copy)]
+/// enum Mode { Slow, Fast }
+///
+/// bitfields! {
+///     #[bitfields(const)]
+///     pub struct Control : u8 {
+///         pub mode: Mode,
+///         pub irq: bool,
+///         pub level: u4,
+///     }
+/// }
+///
+/// const DEFAULT: Control = Control::new(Mode::Slow, false, u4::MIN);
+/// static FAST: Control = DEFAULT.with_mode(Mode::Fast).with_irq(true);
+/// assert_eq!(FAST.0.raw(), 0b11);
+/// ```
+///
 /// The traits derived in the example - [`Debug`], [`Clone`], [`Copy`], [`Default`],
 /// [`PartialEq`], [`Eq`], [`PartialOrd`], [`Ord`] and [`Hash`] - are the only you
 /// can `#[derive()]`. All others need to be manually implemented.
@@ -106,36 +151,379 @@ mod raw;
 /// assert_eq!(instance.0.raw(), 0b101); // `Enum::B` is 1, `Enum::A` is 0.
 /// ```
 ///
+/// To go the other way, use the generated `from_raw()` method (or the [`TryFrom`] impl).
+/// It checks that every field holds a valid value (using [`BitFieldCompatible::is_valid_raw()`])
+/// and that all bits outside of the fields are zero, and returns [`InvalidBits`] otherwise.
+/// If you already know the value is valid, you can use the unsafe `from_raw_unchecked()`.
+/// Both have the same visibility as the constructor, and the [`TryFrom`] impl is only
+/// generated when the constructor is `pub`.
+/// ```rust
+/// # use superbitty::{bitfields, BitFieldCompatible};
+/// #[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+/// enum Enum { A, B, C }
+///
+/// bitfields! {
+///     #[derive(Debug, PartialEq, Eq)]
+///     pub struct Bitfields : u8 {
+///         a: Enum,
+///         b: Enum,
+///     }
+/// }
+///
+/// let instance = Bitfields::from_raw(0b1001).unwrap();
+/// assert_eq!(instance.a(), Enum::B);
+/// assert_eq!(instance.b(), Enum::C);
+///
+/// assert!(Bitfields::from_raw(0b0011).is_err()); // There is no variant with discriminant 3.
+/// assert!(Bitfields::try_from(0b10000).is_err()); // Bits outside of the fields are set.
+/// ```
+///
+/// For putting the bitfields on the wire, `to_le_bytes()`, `to_be_bytes()` and `to_ne_bytes()`
+/// return the raw value as bytes, and `from_le_bytes()`, `from_be_bytes()` and
+/// `from_ne_bytes()` decode them with the same checks as `from_raw()`. The `from_*` methods
+/// have the same visibility as the constructor.
+/// ```rust
+/// # use superbitty::{bitfields, u4, u12};
+/// bitfields! {
+///     #[derive(Debug, PartialEq, Eq)]
+///     pub struct Header : u16 {
+///         pub length: u12,
+///         pub version: u4,
+///     }
+/// }
+///
+/// let header = Header::new(u12::new(0x234).unwrap(), u4::new(1).unwrap());
+/// assert_eq!(header.to_be_bytes(), [0x12, 0x34]);
+/// assert_eq!(Header::from_be_bytes([0x12, 0x34]), Ok(header));
+/// ```
+///
+/// Bitfields structs that are [`Copy`] implement [`BitFieldCompatible`] themselves, so they
+/// can be nested in other bitfields structs. Their
+/// [`BITS_LEN`](BitFieldCompatible::BITS_LEN) is the end of their highest entry (field,
+/// reserved bits or union), including any gaps below it, or the whole width of the base type
+/// with `order = msb0`, since the fields are then at its top.
+/// ```rust
+/// # use superbitty::{bitfields, BitFieldCompatible};
+/// #[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+/// enum Speed { Slow, Medium, Fast }
+///
+/// bitfields! {
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     pub struct Mode : u8 {
+///         pub speed: Speed,
+///         pub reverse: bool,
+///     }
+/// }
+///
+/// bitfields! {
+///     pub struct Control : u32 {
+///         pub enabled: bool,
+///         pub mode: Mode, // from bit 1 to bit 4
+///     }
+/// }
+///
+/// let control = Control::new(true, Mode::new(Speed::Fast, false));
+/// assert_eq!(control.mode().speed(), Speed::Fast);
+/// assert_eq!(control.0.raw(), 0b0101);
+/// ```
+///
+/// Fields can also be arrays of [`BitFieldCompatible`] types, with the elements stored one
+/// after the other. For an array field `field`, `field(index)`, `set_field(index, value)` and
+/// `with_field(index, value)` access a single element (and panic if `index` is out of bounds),
+/// while `field_array()`, `set_field_array()` and `with_field_array()` access the whole array.
+/// ```rust
+/// # use superbitty::{bitfields, u4};
+/// bitfields! {
+///     pub struct Selectors : u32 {
+///         pub channels: [u4; 8],
+///     }
+/// }
+///
+/// let mut selectors = Selectors::new([u4::new(3).unwrap(); 8]);
+/// selectors.set_channels(1, u4::new(5).unwrap());
+/// assert_eq!(selectors.channels(1).value(), 5);
+/// assert_eq!(selectors.0.raw(), 0x3333_3353);
+/// assert_eq!(selectors.channels_array()[0].value(), 3);
+/// ```
+///
+/// By default, each field is placed right after the previous one. A field can instead be
+/// given an explicit position with `#[bits(start..end)]` (or `#[bits(start..=last)]`), or
+/// `#[bit(index)]` for a single bit. The range must be exactly as wide as the field's type,
+/// and fields must not overlap; both are checked at compile time. Fields without a position
+/// that follow a positioned field are placed right after it. Bits not covered by any field
+/// must be zero, as with any other bitfields struct.
+/// ```rust
+/// # use superbitty::{bitfields, u3, u4};
+/// bitfields! {
+///     pub struct Status : u16 {
+///         #[bit(0)]
+///         pub ready: bool,
+///         #[bits(4..8)]
+///         pub mode: u4,
+///         pub level: u3, // from bit 8 to bit 10
+///     }
+/// }
+///
+/// let status = Status::new(true, u4::new(0b1010).unwrap(), u3::new(0b101).unwrap());
+/// assert_eq!(status.0.raw(), 0b101_1010_0001);
+/// ```
+///
+/// Bits can be skipped with a `_: width` entry. Reserved bits take up space in the layout,
+/// but have no accessors, no constructor argument and don't take part in the derived traits.
+/// By default they must be zero, like bits not covered by any field; with
+/// `#[reserved(keep)]` they may hold any value, and setters leave them untouched.
+/// ```rust
+/// # use superbitty::{bitfields, u4};
+/// bitfields! {
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     pub struct Register : u16 {
+///         pub low: u4,
+///         _: 4,
+///         #[reserved(keep)]
+///         _: 4,
+///         pub high: u4,
+///     }
+/// }
+///
+/// let mut register = Register::from_raw(0x5_A_0_3).unwrap();
+/// register.set_low(u4::new(7).unwrap());
+/// assert_eq!(register.0.raw(), 0x5_A_0_7);
+/// assert_eq!(register, Register::new(u4::new(7).unwrap(), u4::new(5).unwrap()));
+/// assert!(Register::from_raw(0x5_A_1_3).is_err());
+/// ```
+///
+/// With `#[bitfields(order = msb0)]`, fields are allocated from the most significant bit
+/// down, as network protocols usually number them, and explicit positions count from the
+/// most significant bit too. The default is `order = lsb0`. The order only applies to the
+/// placement of the fields: the value of a field keeps its own layout, so the elements of an
+/// array field (and of a tuple field) are still packed from the least significant end of the
+/// field, with element 0 in its lowest bits.
+/// ```rust
+/// # use superbitty::{bitfields, u4};
+/// bitfields! {
+///     #[bitfields(order = msb0)]
+///     pub struct VersionAndLength : u8 {
+///         pub version: u4,
+///         pub header_length: u4,
+///     }
+/// }
+///
+/// let value = VersionAndLength::new(u4::new(4).unwrap(), u4::new(5).unwrap());
+/// assert_eq!(value.0.raw(), 0x45);
+/// ```
+///
+/// For layouts wider than 128 bits, the base type can be an array of `u8`, `u32` or `u64`.
+/// Bit `i` is bit `i % W` of element `i / W` (where `W` is the width of an element), and
+/// fields may cross element boundaries. Such structs can only be nested in other bitfields
+/// if they are at most 128 bits wide.
+/// ```rust
+/// # use superbitty::{bitfields, u4, u12};
+/// bitfields! {
+///     pub struct Descriptor : [u8; 16] {
+///         pub kind: u4,
+///         pub length: u12,
+///         pub address: u64,
+///     }
+/// }
+///
+/// let descriptor = Descriptor::new(u4::new(0x3).unwrap(), u12::new(0xFED).unwrap(), 1);
+/// assert_eq!(descriptor.0.raw()[..3], [0xD3, 0xFE, 0x01]);
+/// assert_eq!(descriptor.length().value(), 0xFED);
+/// ```
+///
+/// When the same bits mean different things, a `union { view { fields… }, … }` entry gives
+/// them alternative views. The fields of each view are placed from the start of the union,
+/// and the union is as wide as its widest view. The primary view - the one marked with
+/// `#[primary]`, or the first one - is the one the struct is always valid for: its fields
+/// have the usual accessors, and the constructor, `from_raw()` and the derives use it. The
+/// getters of the fields of other views return `None` when the bits are not valid for them,
+/// and their setters - `set_field()` and `with_field()` - fail with [`InvalidBits`] when
+/// the new value would make the bits invalid for the primary view. For every view `view`,
+/// `is_view_valid()` checks whether the bits are valid for it.
+/// ```rust
+/// # use superbitty::{bitfields, u4, BitFieldCompatible};
+/// #[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+/// #[repr(u8)]
+/// pub enum Addr { Local = 0x01, Broadcast = 0xFF }
+///
+/// bitfields! {
+///     #[derive(Debug)]
+///     pub struct Target : u16 {
+///         union {
+///             nibbles { pub low: u4, pub high: u4 },
+///             #[primary]
+///             addr { pub addr: Addr },
+///         },
+///     }
+/// }
+///
+/// let mut target = Target::new(Addr::Broadcast);
+/// assert_eq!(target.low(), Some(u4::MAX));
+/// assert!(target.set_low(u4::new(3).unwrap()).is_err()); // 0xF3 is not an `Addr`.
+/// assert_eq!(format!("{target:?}"), "Target { addr: Broadcast }");
+/// assert!(target.with_high(u4::MIN).is_err()); // Neither is 0x0F.
+/// ```
+///
 /// [`BitFieldCompatible`]: crate::BitFieldCompatible
 /// [`Debug`]: core::fmt::Debug
 /// [`Hash`]: core::hash::Hash
 /// [`raw()`]: Raw::raw
+/// [`into_raw()`]: BitFieldCompatible::into_raw
+/// [`from_raw()`]: BitFieldCompatible::from_raw
+/// [`is_valid_raw()`]: BitFieldCompatible::is_valid_raw
 pub use superbitty_macros::bitfields;
-/// An enum that can be used as a bitfield.
+/// An enum or a newtype struct that can be used as a bitfield.
 ///
 /// It must be [`Copy`].
 ///
-/// It must carry no payload and have no negative discriminants.
+/// Enums must carry no payload. The discriminants may be any constant expressions.
+///
+/// The derived [`is_valid_raw()`] and [`try_from_raw()`] accept exactly the discriminants
+/// of the enum.
+///
+/// [`is_valid_raw()`]: BitFieldCompatible::is_valid_raw
+/// [`try_from_raw()`]: BitFieldCompatible::try_from_raw
 ///
 /// ```
 /// # use superbitty::BitFieldCompatible;
 /// #[derive(BitFieldCompatible, Clone, Copy)]
 /// enum BitFieldCompatibleEnum { A, B, C }
 /// ```
+///
+/// Enums with a signed `#[repr()]` (or without one) may have negative discriminants. If
+/// they do, they are stored in two's complement within the minimal width needed for all
+/// discriminants, and sign-extended when read back.
+///
+/// ```
+/// # use superbitty::BitFieldCompatible;
+/// #[derive(BitFieldCompatible, Clone, Copy)]
+/// #[repr(i8)]
+/// enum Direction { Backward = -1, Stop = 0, Forward = 1 }
+///
+/// assert_eq!(<Direction as BitFieldCompatible>::BITS_LEN, 2);
+/// assert_eq!(Direction::Backward.into_raw(), 0b11);
+/// ```
+///
+/// Enums with sparse discriminants can be stored more compactly with
+/// `#[bit_field(compact)]`: they are then stored as the index of the variant (in declaration
+/// order) instead of the discriminant, which stays untouched.
+///
+/// ```
+/// # use superbitty::BitFieldCompatible;
+/// #[derive(BitFieldCompatible, Clone, Copy)]
+/// #[bit_field(compact)]
+/// enum Port { Http = 80, Https = 443, Alt = 8080 }
+///
+/// assert_eq!(<Port as BitFieldCompatible>::BITS_LEN, 2);
+/// assert_eq!(Port::Alt.into_raw(), 2);
+/// ```
+///
+/// By default, an enum takes the minimal width its variants need, so adding a variant may
+/// change the layout of every bitfields struct containing it. To keep the layout stable,
+/// the width can be pinned with `#[bit_field(bits = N)]`; the enum then starts at bit zero
+/// and takes exactly `N` bits, and compilation fails if the variants do not fit in them.
+///
+/// ```
+/// # use superbitty::BitFieldCompatible;
+/// #[derive(BitFieldCompatible, Clone, Copy)]
+/// #[bit_field(bits = 4)]
+/// enum Version { V1 = 1, V2 = 2 }
+///
+/// assert_eq!(<Version as BitFieldCompatible>::BITS_LEN, 4);
+/// ```
+///
+/// An enum can also be left open by marking one variant with a single field as
+/// `#[bit_field(catch_all)]`. The field must be a primitive unsigned integer or a
+/// [`BitFieldCompatible`] type, and it gives the enum its width. The other variants are
+/// stored as their discriminants, and every other value decodes to the catch-all, keeping
+/// the raw value. Note that putting a value of another variant in the catch-all makes it
+/// decode back as that variant.
+///
+/// ```
+/// # use superbitty::BitFieldCompatible;
+/// #[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+/// #[repr(u8)]
+/// enum Opcode {
+///     Nop,
+///     Load = 5,
+///     #[bit_field(catch_all)]
+///     Other(u8),
+/// }
+///
+/// assert_eq!(Opcode::try_from_raw(5), Some(Opcode::Load));
+/// assert_eq!(Opcode::try_from_raw(42), Some(Opcode::Other(42)));
+/// ```
+///
+/// Structs must have exactly one field. If it is a primitive unsigned integer, the width
+/// must be given with `#[bit_field(bits = N)]`, and a checked constructor `new()` (with the
+/// visibility of the field) is generated. Values that are too wide (if created in other ways)
+/// are truncated when stored.
+///
+/// If the field is another [`BitFieldCompatible`] type, the struct takes the same bits as
+/// it, unless a (bigger) width is given with `#[bit_field(bits = N)]`.
+///
+/// ```
+/// # use superbitty::{BitFieldCompatible, u3};
+/// #[derive(BitFieldCompatible, Clone, Copy)]
+/// #[bit_field(bits = 6)]
+/// pub struct Counter(pub u8);
+///
+/// assert_eq!(Counter::new(0b111111).map(|counter| counter.0), Some(0b111111));
+/// assert!(Counter::new(0b1000000).is_none());
+///
+/// #[derive(BitFieldCompatible, Clone, Copy)]
+/// struct Mode(u3);
+///
+/// #[derive(BitFieldCompatible, Clone, Copy)]
+/// #[bit_field(bits = 4)]
+/// struct PaddedMode(u3);
+///
+/// assert_eq!(<Mode as BitFieldCompatible>::BITS_LEN, 3);
+/// assert_eq!(<PaddedMode as BitFieldCompatible>::BITS_LEN, 4);
+/// ```
+///
+/// The derive also generates the const conversions that `#[bitfields(const)]` structs use
+/// (see [`bitfields!`]). For a newtype or a catch-all variant over another
+/// [`BitFieldCompatible`] type, they are built from those of the inner type, so unless the
+/// deriving type is generic, the inner type cannot be one that implements the trait manually.
+///
+/// [`bitfields!`]: macro@bitfields
 pub use superbitty_macros::BitFieldCompatible;
 
-pub use crate::raw::Raw;
+pub use crate::fixed::{Fixed, Rounding, UFixed};
+pub use crate::int::*;
+pub use crate::raw::{InvalidBits, Raw};
+pub use crate::uint::*;
 
-/// A type that can be used as a bitfield. This is usually [derived] for enums.
-/// Structs and unions can implement this explicitly, as the safety requirements cannot be
-/// guaranteed for them with `#[derive()]` (at least not easily).
+/// A type that can be used as a bitfield. This is usually [derived] for enums and newtype
+/// structs. Other structs and unions can implement this explicitly, as the safety requirements
+/// cannot be guaranteed for them with `#[derive()]` (at least not easily).
+///
+/// This is also implemented for:
+///
+///  - [`bool`], which takes one bit.
+///  - `u8` to `u64` and their `NonZero` counterparts, which take their full width.
+///  - [`char`], which takes 21 bits.
+///  - [`UInt`]s and [`Int`]s, and the fixed-point [`UFixed`]s and [`Fixed`]s.
+///  - Arrays and tuples of bitfield types, packing the elements one after the other,
+///    starting from the least significant bit.
+///  - [`Option`]s of bitfield types. An `Option` takes the same bits as its inner type if
+///    it has a [`NICHE`], or one more bit (set for `Some`) otherwise.
 ///
 /// # Safety
 ///
 /// [`into_raw()`] must provide a value within the range specified by [`SHIFT`] and [`BITS_LEN`].
 ///
+/// [`is_valid_raw()`] must only return `true` for values that are valid to pass to
+/// [`from_raw()`].
+///
+/// [`NICHE`] must fulfill the requirements listed in its documentation.
+///
 /// [derived]: macro@BitFieldCompatible
+/// [`NICHE`]: BitFieldCompatible::NICHE
 /// [`into_raw()`]: BitFieldCompatible::into_raw
+/// [`from_raw()`]: BitFieldCompatible::from_raw
+/// [`is_valid_raw()`]: BitFieldCompatible::is_valid_raw
 /// [`SHIFT`]: BitFieldCompatible::SHIFT
 /// [`BITS_LEN`]: BitFieldCompatible::BITS_LEN
 pub unsafe trait BitFieldCompatible: Copy {
@@ -158,10 +546,25 @@ pub unsafe trait BitFieldCompatible: Copy {
     /// `0b11` it needs two bits to be stored.
     const BITS_LEN: u32;
 
-    /// This value must not be overridden! Doing so can cause UB.
+    /// This value must not be overridden with a different mask! Doing so can cause UB.
     #[doc(hidden)]
     const BITS_MASK: u128 = (1 << Self::BITS_LEN) - 1;
 
+    /// A raw value that is never used by this type, if there is one. `Option<Self>` uses
+    /// it to represent `None` without taking an extra bit.
+    ///
+    /// It must be within the range specified by [`SHIFT`] and [`BITS_LEN`], [`into_raw()`]
+    /// must never return it and [`is_valid_raw()`] must return `false` for it.
+    ///
+    /// This is computed for derived enums and newtypes and for `bitfields!` structs, and it
+    /// is `None` by default.
+    ///
+    /// [`SHIFT`]: BitFieldCompatible::SHIFT
+    /// [`BITS_LEN`]: BitFieldCompatible::BITS_LEN
+    /// [`into_raw()`]: BitFieldCompatible::into_raw
+    /// [`is_valid_raw()`]: BitFieldCompatible::is_valid_raw
+    const NICHE: Option<u128> = None;
+
     /// Retrieves the raw int representation of a value.
     fn into_raw(self) -> u128;
 
@@ -171,19 +574,124 @@ pub unsafe trait BitFieldCompatible: Copy {
     ///
     /// `v` must be a valid value for this type.
     unsafe fn from_raw(v: u128) -> Self;
+
+    /// Returns whether `v` is a valid raw value for this type, that is, whether it can
+    /// be passed to [`from_raw()`]. This is used to decode untrusted bitfields data, for
+    /// example in the `from_raw()` method `bitfields!` generates.
+    ///
+    /// The default implementation conservatively returns `false`, meaning that bitfields
+    /// containing this type can never be decoded from raw data. Override it to enable that.
+    ///
+    /// [`from_raw()`]: BitFieldCompatible::from_raw
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        _ = v;
+        false
+    }
+
+    /// Safely converts a raw int representation to this type, returning `None` if it is
+    /// not valid.
+    ///
+    /// The default implementation is based on [`is_valid_raw()`] and [`from_raw()`].
+    ///
+    /// [`is_valid_raw()`]: BitFieldCompatible::is_valid_raw
+    /// [`from_raw()`]: BitFieldCompatible::from_raw
+    #[inline]
+    fn try_from_raw(v: u128) -> Option<Self> {
+        if Self::is_valid_raw(v) {
+            // SAFETY: We just checked `v` is valid.
+            Some(unsafe { Self::from_raw(v) })
+        } else {
+            None
+        }
+    }
 }
 
 #[doc(hidden)]
 pub mod __helpers {
     pub use core::clone::Clone;
     pub use core::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+    pub use core::convert::TryFrom;
     pub use core::default::Default;
     pub use core::fmt::{Debug, Formatter, Result as FmtResult};
     pub use core::hash::{Hash, Hasher};
     pub use core::hint::unreachable_unchecked;
     pub use core::marker::Copy;
+    pub use core::mem::{size_of, MaybeUninit};
+    pub use core::option::Option::{self, None, Some};
+    pub use core::result::Result;
+
+    pub use crate::raw::low_bits_mask;
+
     pub type PartialCmpResult = Option<Ordering>;
     pub const SOME_EQ: PartialCmpResult = Some(Ordering::Equal);
+    pub const INVALID_BITS: super::InvalidBits = super::InvalidBits(());
+
+    pub const fn assert_bitfield_compatible<T: super::BitFieldCompatible>() {
+        assert!(
+            T::SHIFT + T::BITS_LEN <= u128::BITS,
+            "field is wider than 128 bits and cannot be used in bitfields",
+        );
+    }
+
+    pub const fn discriminants_shift(discriminants_mask: u128) -> u32 {
+        if discriminants_mask == 0 {
+            0 // Using 128 will panic in debug mode.
+        } else {
+            discriminants_mask.trailing_zeros()
+        }
+    }
+
+    pub const fn discriminants_bits_len(discriminants_mask: u128) -> u32 {
+        if discriminants_mask == 0 {
+            0 // Both leading and trailing zero are 128, subtracting will overflow.
+        } else {
+            u128::BITS - discriminants_mask.trailing_zeros() - discriminants_mask.leading_zeros()
+        }
+    }
+
+    /// Returns the first value within `SHIFT` and `BITS_LEN` that is not one of `raws`.
+    pub const fn find_niche(raws: &[u128], shift: u32, bits_len: u32) -> Option<u128> {
+        // At least one of the first `raws.len() + 1` candidates is unused.
+        let mut candidate = 0;
+        while candidate <= raws.len() as u128 {
+            if bits_len < u128::BITS && candidate >> bits_len != 0 {
+                return None;
+            }
+            let raw = candidate << shift;
+            let mut i = 0;
+            while i < raws.len() && raws[i] != raw {
+                i += 1;
+            }
+            if i == raws.len() {
+                return Some(raw);
+            }
+            candidate += 1;
+        }
+        None
+    }
 
-    pub const fn assert_bitfield_compatible<T: super::BitFieldCompatible>() {}
+    /// Returns the width needed to store all discriminants in two's complement,
+    /// or `None` if none of them is negative.
+    pub const fn negative_discriminants_bits_len(discriminants: &[i128]) -> Option<u32> {
+        let mut has_negative = false;
+        let mut bits_len = 0;
+        let mut i = 0;
+        while i < discriminants.len() {
+            let discriminant = discriminants[i];
+            has_negative |= discriminant < 0;
+            // Without the sign bit: the significant bits of `x`, or of `!x` for negative `x`.
+            let magnitude = if discriminant < 0 { !discriminant } else { discriminant };
+            let discriminant_bits_len = i128::BITS - magnitude.leading_zeros() + 1;
+            if discriminant_bits_len > bits_len {
+                bits_len = discriminant_bits_len;
+            }
+            i += 1;
+        }
+        if has_negative {
+            Some(bits_len)
+        } else {
+            None
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use core::fmt;
+
 /// A raw representation of a bitfield with base type `T`. This
 /// is used for storing the bitfields data.
 ///
@@ -27,7 +29,7 @@ impl<T: OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields
     }
 
     /// The raw value of this bitfields.
-    pub fn raw(self) -> T {
+    pub const fn raw(self) -> T {
         self.0
     }
 
@@ -44,11 +46,171 @@ impl<T: OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields
     }
 }
 
+/// The error returned when decoding bitfields from a raw value that contains
+/// invalid bit patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidBits(pub(crate) ());
+
+impl fmt::Display for InvalidBits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid bit pattern for bitfields")
+    }
+}
+
+impl core::error::Error for InvalidBits {}
+
 // This trait should not be exposed, it is sealed.
 pub trait OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields: Copy {}
-impl OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields for u8 {}
-impl OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields for u16 {}
-impl OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields for u32 {}
-impl OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields for u64 {}
-impl OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields for u128 {}
-impl OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields for usize {}
+
+/// A mask of the `len` lowest bits.
+pub const fn low_bits_mask(len: u32) -> u128 {
+    match 1u128.checked_shl(len) {
+        Some(bit) => bit - 1,
+        None => u128::MAX,
+    }
+}
+
+// The methods below are used by `bitfields!` to access the bits of any base type in the
+// same way. They are inherent (and not on a trait) so that they can be `const`.
+
+macro_rules! primitive_raw {
+    ( $( $ty:ident )* ) => {
+        $(
+            impl OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields for $ty {}
+
+            impl Raw<$ty> {
+                #[doc(hidden)]
+                pub const BITS: u32 = $ty::BITS;
+
+                /// # Safety
+                ///
+                /// Same as [`Raw::new()`].
+                #[doc(hidden)]
+                #[inline]
+                pub const unsafe fn zeroed() -> Self {
+                    Self(0)
+                }
+
+                /// The `len` bits starting at `offset`. `len` must be at most 128.
+                #[doc(hidden)]
+                #[inline]
+                pub const fn get_bits(&self, offset: u32, len: u32) -> u128 {
+                    match (self.0 as u128).checked_shr(offset) {
+                        Some(bits) => bits & low_bits_mask(len),
+                        None => 0,
+                    }
+                }
+
+                /// Replaces the `len` bits starting at `offset` with the low bits of `value`.
+                ///
+                /// # Safety
+                ///
+                /// Same as [`Raw::get_mut()`].
+                #[doc(hidden)]
+                #[inline]
+                pub const unsafe fn set_bits(&mut self, offset: u32, len: u32, value: u128) {
+                    if len == 0 {
+                        return;
+                    }
+                    let mask = low_bits_mask(len) << offset;
+                    self.0 = ((self.0 as u128 & !mask) | ((value << offset) & mask)) as $ty;
+                }
+
+                #[doc(hidden)]
+                #[inline]
+                pub const fn is_zero(&self) -> bool {
+                    self.0 == 0
+                }
+            }
+        )*
+    };
+}
+primitive_raw!(u8 u16 u32 u64 u128 usize);
+
+/// Arrays store the bits in little-endian word order: bit `i` is bit `i % W` of
+/// element `i / W`, where `W` is the width of the element.
+macro_rules! array_raw {
+    ( $( $word:ident )* ) => {
+        $(
+            impl<const N: usize> OnlyPrimitiveUnsignedIntegersAreAllowedAsBaseTypesForSuperbittyBitfields
+                for [$word; N]
+            {
+            }
+
+            impl<const N: usize> Raw<[$word; N]> {
+                #[doc(hidden)]
+                pub const BITS: u32 = $word::BITS * N as u32;
+
+                /// # Safety
+                ///
+                /// Same as [`Raw::new()`].
+                #[doc(hidden)]
+                #[inline]
+                pub const unsafe fn zeroed() -> Self {
+                    Self([0; N])
+                }
+
+                /// The `len` bits starting at `offset`. `len` must be at most 128.
+                #[doc(hidden)]
+                #[inline]
+                pub const fn get_bits(&self, offset: u32, len: u32) -> u128 {
+                    let mut result = 0;
+                    let mut done = 0;
+                    while done < len {
+                        let bit = offset + done;
+                        let in_word = bit % $word::BITS;
+                        let chunk_len = min($word::BITS - in_word, len - done);
+                        let chunk = (self.0[(bit / $word::BITS) as usize] >> in_word) as u128
+                            & low_bits_mask(chunk_len);
+                        result |= chunk << done;
+                        done += chunk_len;
+                    }
+                    result
+                }
+
+                /// Replaces the `len` bits starting at `offset` with the low bits of `value`.
+                ///
+                /// # Safety
+                ///
+                /// Same as [`Raw::get_mut()`].
+                #[doc(hidden)]
+                #[inline]
+                pub const unsafe fn set_bits(&mut self, offset: u32, len: u32, value: u128) {
+                    let mut done = 0;
+                    while done < len {
+                        let bit = offset + done;
+                        let in_word = bit % $word::BITS;
+                        let chunk_len = min($word::BITS - in_word, len - done);
+                        let mask = (low_bits_mask(chunk_len) as $word) << in_word;
+                        let chunk = ((value >> done) as $word) << in_word;
+                        let word = &mut self.0[(bit / $word::BITS) as usize];
+                        *word = (*word & !mask) | (chunk & mask);
+                        done += chunk_len;
+                    }
+                }
+
+                #[doc(hidden)]
+                #[inline]
+                pub const fn is_zero(&self) -> bool {
+                    let mut i = 0;
+                    while i < N {
+                        if self.0[i] != 0 {
+                            return false;
+                        }
+                        i += 1;
+                    }
+                    true
+                }
+            }
+        )*
+    };
+}
+array_raw!(u8 u32 u64);
+
+const fn min(a: u32, b: u32) -> u32 {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
@@ -0,0 +1,396 @@
+use core::fmt;
+use core::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign,
+    Mul, MulAssign, Not, Rem, RemAssign, Sub, SubAssign,
+};
+
+use crate::BitFieldCompatible;
+
+/// An unsigned integer with `BITS` bits, where `BITS` is between 1 and 127 (inclusive).
+///
+/// There are also type aliases for all widths, from [`u1`] to [`u127`] (except those of
+/// the primitive types).
+///
+/// Arithmetic operators behave like those of the primitive integers: they panic on overflow
+/// when debug assertions are enabled, and wrap around otherwise.
+///
+/// ```
+/// # use superbitty::{bitfields, u3, u5, BitFieldCompatible};
+/// bitfields! {
+///     pub struct Counters : u8 {
+///         pub small: u3,
+///         pub big: u5,
+///     }
+/// }
+///
+/// let mut counters = Counters::new(u3::new(5).unwrap(), u5::MAX);
+/// assert_eq!(counters.small().value(), 5);
+/// assert_eq!(counters.big().value(), 31);
+///
+/// counters.set_small(counters.small() + u3::new(2).unwrap());
+/// assert_eq!(counters.small(), u3::MAX);
+/// assert_eq!(u3::new(8), None);
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UInt<const BITS: u32>(u128);
+
+/// The error returned when converting an integer that does not fit in the target type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfRangeError(pub(crate) ());
+
+impl fmt::Display for OutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("out of range integral type conversion attempted")
+    }
+}
+
+impl core::error::Error for OutOfRangeError {}
+
+impl<const BITS: u32> UInt<BITS> {
+    /// The size of this integer type in bits.
+    pub const BITS: u32 = {
+        assert!(BITS >= 1 && BITS <= 127, "`UInt` must have between 1 and 127 bits");
+        BITS
+    };
+
+    /// The smallest value that can be represented by this integer type (zero).
+    pub const MIN: Self = Self(0);
+
+    /// The largest value that can be represented by this integer type (`2^BITS - 1`).
+    pub const MAX: Self = Self(u128::MAX >> (u128::BITS - Self::BITS));
+
+    /// Creates a new integer, or returns `None` if `value` does not fit in `BITS` bits.
+    #[inline]
+    pub const fn new(value: u128) -> Option<Self> {
+        if value <= Self::MAX.0 {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a new integer, truncating `value` to its low `BITS` bits.
+    #[inline]
+    pub const fn new_wrapping(value: u128) -> Self {
+        Self(value & Self::MAX.0)
+    }
+
+    /// Creates a new integer, clamping `value` to [`MAX`](Self::MAX).
+    #[inline]
+    pub const fn new_saturating(value: u128) -> Self {
+        if value <= Self::MAX.0 {
+            Self(value)
+        } else {
+            Self::MAX
+        }
+    }
+
+    /// Creates a new integer without checking that `value` fits in `BITS` bits.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be less than or equal to [`MAX`](Self::MAX).
+    #[inline]
+    pub const unsafe fn new_unchecked(value: u128) -> Self {
+        Self(value)
+    }
+
+    /// The value of this integer.
+    #[inline]
+    pub const fn value(self) -> u128 {
+        self.0
+    }
+
+    /// Checked addition. Returns `None` on overflow.
+    #[inline]
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        // Both are less than 2^127, so this cannot overflow `u128`.
+        Self::new(self.0 + rhs.0)
+    }
+
+    /// Checked subtraction. Returns `None` on overflow.
+    #[inline]
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(result) => Some(Self(result)),
+            None => None,
+        }
+    }
+
+    /// Checked multiplication. Returns `None` on overflow.
+    #[inline]
+    pub const fn checked_mul(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_mul(rhs.0) {
+            Some(result) => Self::new(result),
+            None => None,
+        }
+    }
+
+    /// Checked division. Returns `None` if `rhs` is zero.
+    #[inline]
+    pub const fn checked_div(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_div(rhs.0) {
+            Some(result) => Some(Self(result)),
+            None => None,
+        }
+    }
+
+    /// Checked remainder. Returns `None` if `rhs` is zero.
+    #[inline]
+    pub const fn checked_rem(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_rem(rhs.0) {
+            Some(result) => Some(Self(result)),
+            None => None,
+        }
+    }
+
+    /// Wrapping (modular) addition.
+    #[inline]
+    pub const fn wrapping_add(self, rhs: Self) -> Self {
+        Self::new_wrapping(self.0 + rhs.0)
+    }
+
+    /// Wrapping (modular) subtraction.
+    #[inline]
+    pub const fn wrapping_sub(self, rhs: Self) -> Self {
+        Self::new_wrapping(self.0.wrapping_sub(rhs.0))
+    }
+
+    /// Wrapping (modular) multiplication.
+    #[inline]
+    pub const fn wrapping_mul(self, rhs: Self) -> Self {
+        Self::new_wrapping(self.0.wrapping_mul(rhs.0))
+    }
+
+    /// Saturating addition.
+    #[inline]
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self::new_saturating(self.0 + rhs.0)
+    }
+
+    /// Saturating subtraction.
+    #[inline]
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Saturating multiplication.
+    #[inline]
+    pub const fn saturating_mul(self, rhs: Self) -> Self {
+        Self::new_saturating(self.0.saturating_mul(rhs.0))
+    }
+}
+
+// Const versions of the conversions, for `#[bitfields(const)]` structs.
+impl<const BITS: u32> UInt<BITS> {
+    #[doc(hidden)]
+    #[inline]
+    pub const fn __superbitty_into_raw(self) -> u128 {
+        self.0
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub const unsafe fn __superbitty_from_raw(v: u128) -> Self {
+        Self(v)
+    }
+}
+
+// SAFETY: The value is always at most `BITS` bits long.
+unsafe impl<const BITS: u32> BitFieldCompatible for UInt<BITS> {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = Self::BITS;
+
+    #[inline]
+    fn into_raw(self) -> u128 {
+        Self::__superbitty_into_raw(self)
+    }
+
+    #[inline]
+    unsafe fn from_raw(v: u128) -> Self {
+        // SAFETY: Guaranteed by the caller.
+        unsafe { Self::__superbitty_from_raw(v) }
+    }
+
+    #[inline]
+    fn is_valid_raw(v: u128) -> bool {
+        v <= Self::MAX.0
+    }
+}
+
+macro_rules! arith_ops {
+    ( $ty:ident; $( $trait:ident :: $method:ident, $assign_trait:ident :: $assign_method:ident
+        => $checked:ident, $wrapping:expr, $msg:literal; )* ) => {
+        $(
+            impl<const BITS: u32> $trait for $ty<BITS> {
+                type Output = Self;
+
+                #[inline]
+                #[track_caller]
+                fn $method(self, rhs: Self) -> Self {
+                    if cfg!(debug_assertions) {
+                        match self.$checked(rhs) {
+                            Some(result) => result,
+                            None => panic!($msg),
+                        }
+                    } else {
+                        #[allow(clippy::redundant_closure_call)]
+                        ($wrapping)(self, rhs)
+                    }
+                }
+            }
+
+            impl<const BITS: u32> $assign_trait for $ty<BITS> {
+                #[inline]
+                #[track_caller]
+                fn $assign_method(&mut self, rhs: Self) {
+                    *self = $trait::$method(*self, rhs);
+                }
+            }
+        )*
+    };
+}
+pub(crate) use arith_ops;
+
+arith_ops! {
+    UInt;
+    Add::add, AddAssign::add_assign
+        => checked_add, Self::wrapping_add, "attempt to add with overflow";
+    Sub::sub, SubAssign::sub_assign
+        => checked_sub, Self::wrapping_sub, "attempt to subtract with overflow";
+    Mul::mul, MulAssign::mul_assign
+        => checked_mul, Self::wrapping_mul, "attempt to multiply with overflow";
+    Div::div, DivAssign::div_assign
+        => checked_div, |a: Self, b: Self| Self(a.0 / b.0), "attempt to divide by zero";
+    Rem::rem, RemAssign::rem_assign
+        => checked_rem, |a: Self, b: Self| Self(a.0 % b.0),
+        "attempt to calculate the remainder with a divisor of zero";
+}
+
+macro_rules! bit_ops {
+    ( $ty:ident; $( $trait:ident :: $method:ident, $assign_trait:ident :: $assign_method:ident => $op:tt; )* ) => {
+        $(
+            impl<const BITS: u32> $trait for $ty<BITS> {
+                type Output = Self;
+
+                #[inline]
+                fn $method(self, rhs: Self) -> Self {
+                    Self(self.0 $op rhs.0)
+                }
+            }
+
+            impl<const BITS: u32> $assign_trait for $ty<BITS> {
+                #[inline]
+                fn $assign_method(&mut self, rhs: Self) {
+                    *self = $trait::$method(*self, rhs);
+                }
+            }
+        )*
+    };
+}
+pub(crate) use bit_ops;
+
+bit_ops! {
+    UInt;
+    BitAnd::bitand, BitAndAssign::bitand_assign => &;
+    BitOr::bitor, BitOrAssign::bitor_assign => |;
+    BitXor::bitxor, BitXorAssign::bitxor_assign => ^;
+}
+
+impl<const BITS: u32> Not for UInt<BITS> {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self {
+        Self::new_wrapping(!self.0)
+    }
+}
+
+macro_rules! fmt_impls {
+    ( $ty:ident; $($trait:ident)* ) => {
+        $(
+            impl<const BITS: u32> fmt::$trait for $ty<BITS> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    fmt::$trait::fmt(&self.0, f)
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use fmt_impls;
+
+fmt_impls!(UInt; Debug Display Binary Octal LowerHex UpperHex);
+
+macro_rules! primitive_conversions {
+    ( $($primitive:ident)* ) => {
+        $(
+            impl<const BITS: u32> TryFrom<$primitive> for UInt<BITS> {
+                type Error = OutOfRangeError;
+
+                #[inline]
+                fn try_from(value: $primitive) -> Result<Self, OutOfRangeError> {
+                    Self::new(value as u128).ok_or(OutOfRangeError(()))
+                }
+            }
+
+            impl<const BITS: u32> TryFrom<UInt<BITS>> for $primitive {
+                type Error = OutOfRangeError;
+
+                #[inline]
+                fn try_from(value: UInt<BITS>) -> Result<Self, OutOfRangeError> {
+                    $primitive::try_from(value.0).map_err(|_| OutOfRangeError(()))
+                }
+            }
+        )*
+    };
+}
+
+primitive_conversions!(u8 u16 u32 u64 usize);
+
+impl<const BITS: u32> TryFrom<u128> for UInt<BITS> {
+    type Error = OutOfRangeError;
+
+    #[inline]
+    fn try_from(value: u128) -> Result<Self, OutOfRangeError> {
+        Self::new(value).ok_or(OutOfRangeError(()))
+    }
+}
+
+impl<const BITS: u32> From<UInt<BITS>> for u128 {
+    #[inline]
+    fn from(value: UInt<BITS>) -> Self {
+        value.0
+    }
+}
+
+macro_rules! aliases {
+    ( $ty:ident, $kind:literal; $($name:ident = $bits:literal)* ) => {
+        $(
+            #[allow(non_camel_case_types)]
+            #[doc = concat!("A ", stringify!($bits), "-bit ", $kind, " integer.")]
+            pub type $name = $ty<$bits>;
+        )*
+    };
+}
+pub(crate) use aliases;
+
+aliases! {
+    UInt, "unsigned";
+    u1 = 1 u2 = 2 u3 = 3 u4 = 4 u5 = 5 u6 = 6 u7 = 7
+    u9 = 9 u10 = 10 u11 = 11 u12 = 12 u13 = 13 u14 = 14 u15 = 15
+    u17 = 17 u18 = 18 u19 = 19 u20 = 20 u21 = 21 u22 = 22 u23 = 23 u24 = 24
+    u25 = 25 u26 = 26 u27 = 27 u28 = 28 u29 = 29 u30 = 30 u31 = 31
+    u33 = 33 u34 = 34 u35 = 35 u36 = 36 u37 = 37 u38 = 38 u39 = 39 u40 = 40
+    u41 = 41 u42 = 42 u43 = 43 u44 = 44 u45 = 45 u46 = 46 u47 = 47 u48 = 48
+    u49 = 49 u50 = 50 u51 = 51 u52 = 52 u53 = 53 u54 = 54 u55 = 55 u56 = 56
+    u57 = 57 u58 = 58 u59 = 59 u60 = 60 u61 = 61 u62 = 62 u63 = 63
+    u65 = 65 u66 = 66 u67 = 67 u68 = 68 u69 = 69 u70 = 70 u71 = 71 u72 = 72
+    u73 = 73 u74 = 74 u75 = 75 u76 = 76 u77 = 77 u78 = 78 u79 = 79 u80 = 80
+    u81 = 81 u82 = 82 u83 = 83 u84 = 84 u85 = 85 u86 = 86 u87 = 87 u88 = 88
+    u89 = 89 u90 = 90 u91 = 91 u92 = 92 u93 = 93 u94 = 94 u95 = 95 u96 = 96
+    u97 = 97 u98 = 98 u99 = 99 u100 = 100 u101 = 101 u102 = 102 u103 = 103 u104 = 104
+    u105 = 105 u106 = 106 u107 = 107 u108 = 108 u109 = 109 u110 = 110 u111 = 111 u112 = 112
+    u113 = 113 u114 = 114 u115 = 115 u116 = 116 u117 = 117 u118 = 118 u119 = 119 u120 = 120
+    u121 = 121 u122 = 122 u123 = 123 u124 = 124 u125 = 125 u126 = 126 u127 = 127
+}
@@ -1,4 +1,4 @@
-use superbitty::BitFieldCompatible;
+use superbitty::{bitfields, BitFieldCompatible};
 
 fn assert_values<T: BitFieldCompatible>(shift: u32, bits_len: u32, bits_mask: u128) {
     assert_eq!(T::SHIFT, shift);
@@ -47,3 +47,515 @@ fn test() {
     assert_values::<Dense>(2, 6, 0b00111111);
     assert_values::<BigOne>(0, 7, 0b1111111);
 }
+
+#[test]
+fn bool() {
+    assert_values::<bool>(0, 1, 0b1);
+    assert_eq!(true.into_raw(), 1);
+    assert_eq!(false.into_raw(), 0);
+    // SAFETY: 0 and 1 are valid values for `bool`.
+    unsafe {
+        assert!(bool::from_raw(1));
+        assert!(!bool::from_raw(0));
+    }
+}
+
+#[test]
+fn is_valid_raw() {
+    assert!(OneZeroVariantEnum::is_valid_raw(0));
+    assert!(!OneZeroVariantEnum::is_valid_raw(1));
+    assert!(Scattered::is_valid_raw(0b0010000000000000));
+    assert!(!Scattered::is_valid_raw(0b0010000000000100));
+    assert!(bool::is_valid_raw(1));
+    assert!(!bool::is_valid_raw(2));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Even(u8);
+
+// SAFETY: `into_raw()` returns at most 7 bits, and `is_valid_raw()` checks the same range.
+unsafe impl BitFieldCompatible for Even {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = 7;
+    fn into_raw(self) -> u128 {
+        self.0 as u128 & 0x7F
+    }
+    unsafe fn from_raw(v: u128) -> Self {
+        Self(v as u8)
+    }
+    fn is_valid_raw(v: u128) -> bool {
+        v < 0x80 && v & 1 == 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct NoValidation;
+
+// SAFETY: `into_raw()` always returns zero.
+unsafe impl BitFieldCompatible for NoValidation {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = 0;
+    fn into_raw(self) -> u128 {
+        0
+    }
+    unsafe fn from_raw(_v: u128) -> Self {
+        Self
+    }
+}
+
+#[test]
+fn try_from_raw() {
+    assert!(matches!(Scattered::try_from_raw(0b100), Some(Scattered::B)));
+    assert!(Scattered::try_from_raw(0b101).is_none());
+    assert!(Dense::try_from_raw(0b11).is_none());
+    assert_eq!(bool::try_from_raw(1), Some(true));
+    assert_eq!(bool::try_from_raw(2), None);
+
+    assert_eq!(Even::try_from_raw(10), Some(Even(10)));
+    assert_eq!(Even::try_from_raw(11), None);
+    assert_eq!(Even::try_from_raw(0x80), None);
+    // Types that don't override `is_valid_raw()` cannot be decoded.
+    assert_eq!(NoValidation::try_from_raw(0), None);
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[bit_field(bits = 6)]
+struct SixBits(u8);
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[bit_field(bits = 20)]
+struct Named {
+    value: u32,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+struct Transparent(Scattered);
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(bits = 16)]
+struct Padded(Scattered);
+
+#[test]
+fn newtypes() {
+    assert_values::<SixBits>(0, 6, 0b111111);
+    assert_values::<Named>(0, 20, 0xFFFFF);
+    assert_values::<Transparent>(2, 12, 0b111111111111);
+    assert_values::<Padded>(0, 16, 0xFFFF);
+
+    assert_eq!(SixBits::new(63), Some(SixBits(63)));
+    assert_eq!(SixBits::new(64), None);
+    assert_eq!(Named::new(0xFFFFF), Some(Named { value: 0xFFFFF }));
+    assert_eq!(Named::new(0x100000), None);
+
+    // Values created without the constructor are truncated.
+    assert_eq!(SixBits(0b1100_0001).into_raw(), 0b1);
+    assert_eq!(SixBits::try_from_raw(0b111111), Some(SixBits(0b111111)));
+    assert_eq!(SixBits::try_from_raw(0b1000000), None);
+
+    assert_eq!(Transparent(Scattered::C).into_raw(), 0b0010000000000000);
+    assert!(Transparent::is_valid_raw(0b100));
+    assert!(!Transparent::is_valid_raw(0b101));
+    assert_eq!(Padded(Scattered::B).into_raw(), 0b100);
+    assert!(Padded::is_valid_raw(0b100));
+    assert!(!Padded::is_valid_raw(0b1000));
+}
+
+const FLAG_X: isize = 0b0100;
+const FLAG_Y: isize = 0b1000;
+
+mod consts {
+    pub const MODE_C: isize = 0b10000;
+}
+
+const fn double(v: isize) -> isize {
+    v * 2
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+enum ConstExpressions {
+    A = 1 << 3,
+    B = FLAG_X | FLAG_Y,
+    C = consts::MODE_C,
+    D = double(consts::MODE_C),
+    E, // Implicitly `D + 1`.
+}
+
+// `repr(u128)` is only stable since Rust 1.89.
+#[rustversion::since(1.89)]
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[repr(u128)]
+enum Huge {
+    A = 1 << 100,
+    B = 1 << 127,
+}
+
+#[test]
+fn const_expressions() {
+    assert_values::<ConstExpressions>(0, 6, 0b111111);
+    assert_eq!(ConstExpressions::E.into_raw(), 0b100001);
+    assert_eq!(ConstExpressions::try_from_raw(0b1100), Some(ConstExpressions::B));
+    assert_eq!(ConstExpressions::try_from_raw(0b100001), Some(ConstExpressions::E));
+    assert_eq!(ConstExpressions::try_from_raw(0b100), None);
+}
+
+#[rustversion::since(1.89)]
+#[test]
+fn const_expressions_u128() {
+    assert_values::<Huge>(100, 28, 0xFFFFFFF);
+    assert_eq!(Huge::try_from_raw(1 << 127), Some(Huge::B));
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+enum Opcode {
+    Nop,
+    Load = 5,
+    Store, // Implicitly `Load + 1`.
+    #[bit_field(catch_all)]
+    Other(u8),
+    Halt, // Implicitly `Other + 1`.
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+enum Command {
+    Reset = 2,
+    #[bit_field(catch_all)]
+    Unknown {
+        raw: SixBits,
+    },
+}
+
+#[test]
+fn catch_all() {
+    assert_values::<Opcode>(0, 8, 0xFF);
+    assert_eq!(Opcode::Nop.into_raw(), 0);
+    assert_eq!(Opcode::Store.into_raw(), 6);
+    assert_eq!(Opcode::Halt.into_raw(), 8);
+    assert_eq!(Opcode::Other(100).into_raw(), 100);
+    assert_eq!(Opcode::try_from_raw(5), Some(Opcode::Load));
+    assert_eq!(Opcode::try_from_raw(8), Some(Opcode::Halt));
+    assert_eq!(Opcode::try_from_raw(7), Some(Opcode::Other(7)));
+    assert_eq!(Opcode::try_from_raw(0xFF), Some(Opcode::Other(0xFF)));
+    assert_eq!(Opcode::try_from_raw(0x100), None);
+    assert!((0..=0xFF).all(Opcode::is_valid_raw));
+
+    assert_values::<Command>(0, 6, 0b111111);
+    assert_eq!(Command::try_from_raw(2), Some(Command::Reset));
+    assert_eq!(
+        Command::try_from_raw(0b101010),
+        Some(Command::Unknown { raw: SixBits::new(0b101010).unwrap() }),
+    );
+    assert_eq!(Command::try_from_raw(0b1000000), None);
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[repr(i8)]
+enum Direction {
+    Backward = -1,
+    Stop = 0,
+    Forward = 1,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+enum Offset {
+    Min = -128,
+    Max = 127,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[repr(i16)]
+enum NonNegative {
+    A = 0b100,
+    B = 0b1100,
+}
+
+bitfields! {
+    struct Motion : u16 {
+        direction: Direction,
+        offset: Offset,
+    }
+}
+
+#[test]
+fn negative_discriminants() {
+    assert_values::<Direction>(0, 2, 0b11);
+    assert_eq!(Direction::Backward.into_raw(), 0b11);
+    assert_eq!(Direction::Forward.into_raw(), 0b01);
+    assert_eq!(Direction::try_from_raw(0b11), Some(Direction::Backward));
+    assert_eq!(Direction::try_from_raw(0b00), Some(Direction::Stop));
+    assert_eq!(Direction::try_from_raw(0b10), None);
+    assert_eq!(Direction::try_from_raw(u128::MAX), None);
+
+    assert_values::<Offset>(0, 8, 0xFF);
+    assert_eq!(Offset::Min.into_raw(), 0x80);
+    assert_eq!(Offset::try_from_raw(0x80), Some(Offset::Min));
+    assert_eq!(Offset::try_from_raw(0x7F), Some(Offset::Max));
+
+    // Signed enums without negative discriminants are stored as before.
+    assert_values::<NonNegative>(2, 2, 0b11);
+    assert_eq!(NonNegative::B.into_raw(), 0b1100);
+    assert_eq!(NonNegative::try_from_raw(0b100), Some(NonNegative::A));
+
+    let motion = Motion::new(Direction::Backward, Offset::Min);
+    assert_eq!(motion.0.raw(), 0b10_0000_0011);
+    let motion = Motion::from_raw(0b01_1111_1111).unwrap();
+    assert_eq!(motion.direction(), Direction::Backward);
+    assert_eq!(motion.offset(), Offset::Max);
+}
+
+// `repr(i128)` is only stable since Rust 1.89.
+#[rustversion::since(1.89)]
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[repr(i128)]
+enum Extremes {
+    Min = i128::MIN,
+    Max = i128::MAX,
+}
+
+#[rustversion::since(1.89)]
+#[test]
+fn negative_discriminants_i128() {
+    assert_values::<Extremes>(0, 128, u128::MAX);
+    assert_eq!(Extremes::Min.into_raw(), 1 << 127);
+    assert_eq!(Extremes::try_from_raw(i128::MAX as u128), Some(Extremes::Max));
+    assert_eq!(Extremes::try_from_raw(0), None);
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[bit_field(compact)]
+enum Sparse {
+    A = 0,
+    B = 1000,
+    C = 5000,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[bit_field(compact)]
+enum SingleCompact {
+    A = 0b1010,
+}
+
+#[test]
+fn compact() {
+    assert_values::<Sparse>(0, 2, 0b11);
+    assert_eq!(Sparse::A.into_raw(), 0);
+    assert_eq!(Sparse::C.into_raw(), 2);
+    assert_eq!(Sparse::C as isize, 5000);
+    assert_eq!(Sparse::try_from_raw(1), Some(Sparse::B));
+    assert_eq!(Sparse::try_from_raw(3), None);
+
+    assert_values::<SingleCompact>(0, 0, 0);
+    assert_eq!(SingleCompact::try_from_raw(0), Some(SingleCompact::A));
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[bit_field(bits = 4)]
+enum Pinned {
+    A = 0b10,
+    B = 0b100,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[bit_field(bits = 4)]
+#[repr(i8)]
+enum PinnedSigned {
+    Minus = -1,
+    Plus = 1,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[bit_field(compact, bits = 3)]
+enum PinnedCompact {
+    A = 100,
+    B = 200,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+#[bit_field(bits = 8)]
+#[repr(u8)]
+enum PinnedCatchAll {
+    A,
+    #[bit_field(catch_all)]
+    Other(superbitty::u4),
+}
+
+bitfields! {
+    struct PinnedFields : u16 {
+        pinned: Pinned,
+        after: bool,
+    }
+}
+
+#[test]
+fn pinned_width() {
+    assert_values::<Pinned>(0, 4, 0b1111);
+    assert_eq!(Pinned::B.into_raw(), 0b100);
+    assert_eq!(Pinned::try_from_raw(0b10), Some(Pinned::A));
+    assert_eq!(Pinned::try_from_raw(0b1000), None);
+    let fields = PinnedFields::new(Pinned::B, true);
+    assert_eq!(fields.0.raw(), 0b1_0100);
+    assert_eq!(fields.pinned(), Pinned::B);
+    assert!(fields.after());
+
+    assert_values::<PinnedSigned>(0, 4, 0b1111);
+    assert_eq!(PinnedSigned::Minus.into_raw(), 0b1111);
+    assert_eq!(PinnedSigned::try_from_raw(0b1111), Some(PinnedSigned::Minus));
+    assert_eq!(PinnedSigned::try_from_raw(0b11), None);
+
+    assert_values::<PinnedCompact>(0, 3, 0b111);
+    assert_eq!(PinnedCompact::B.into_raw(), 1);
+
+    assert_values::<PinnedCatchAll>(0, 8, 0xFF);
+    assert_eq!(
+        PinnedCatchAll::try_from_raw(0b1111),
+        Some(PinnedCatchAll::Other(superbitty::u4::new(0b1111).unwrap())),
+    );
+    assert_eq!(PinnedCatchAll::try_from_raw(0b1_0000), None);
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+enum ThreeModes {
+    A,
+    B,
+    C,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy, Debug, PartialEq)]
+enum ShiftedModes {
+    A = 0b100,
+    B = 0b1000,
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct OptionalModes : u16 {
+        mode: Option<ThreeModes>,
+        flag: Option<bool>,
+        nested: Option<Option<bool>>,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct FlagAndMode : u8 {
+        flag: bool,
+        mode: ThreeModes,
+    }
+}
+
+#[test]
+fn option() {
+    // `ThreeModes` has an unused bit pattern, 0b11.
+    assert_eq!(ThreeModes::NICHE, Some(0b11));
+    assert_values::<Option<ThreeModes>>(0, 2, 0b11);
+    assert_eq!(None::<ThreeModes>.into_raw(), 0b11);
+    assert_eq!(Some(ThreeModes::B).into_raw(), 0b01);
+    assert_eq!(Option::<ThreeModes>::try_from_raw(0b11), Some(None));
+    assert_eq!(Option::<ThreeModes>::try_from_raw(0b10), Some(Some(ThreeModes::C)));
+    assert_eq!(Option::<ThreeModes>::NICHE, None);
+
+    assert_eq!(ShiftedModes::NICHE, Some(0));
+    assert_values::<Option<ShiftedModes>>(2, 2, 0b11);
+    assert_eq!(None::<ShiftedModes>.into_raw(), 0);
+    assert_eq!(Option::<ShiftedModes>::try_from_raw(0b1000), Some(Some(ShiftedModes::B)));
+
+    // `bool` uses all of its bit patterns, so an extra bit is needed.
+    assert_eq!(bool::NICHE, None);
+    assert_values::<Option<bool>>(0, 2, 0b11);
+    assert_eq!(None::<bool>.into_raw(), 0b00);
+    assert_eq!(Some(false).into_raw(), 0b10);
+    assert_eq!(Some(true).into_raw(), 0b11);
+    assert_eq!(Option::<bool>::try_from_raw(0b01), None);
+    assert_eq!(Option::<bool>::try_from_raw(0b11), Some(Some(true)));
+
+    // ... which leaves a niche for an outer `Option`.
+    assert_values::<Option<Option<bool>>>(0, 2, 0b11);
+    assert_eq!(None::<Option<bool>>.into_raw(), 0b01);
+    assert_eq!(Option::<Option<bool>>::try_from_raw(0b01), Some(None));
+    assert_eq!(Option::<Option<bool>>::try_from_raw(0b00), Some(Some(None)));
+
+    let mut modes = OptionalModes::new(None, Some(true), Some(None));
+    assert_eq!(modes.0.raw(), 0b00_11_11);
+    assert_eq!(modes.mode(), None);
+    modes.set_mode(Some(ThreeModes::C));
+    assert_eq!(modes.mode(), Some(ThreeModes::C));
+    assert_eq!(OptionalModes::from_raw(modes.0.raw()), Ok(modes));
+    assert!(OptionalModes::from_raw(0b00_01_00).is_err());
+
+    // Bitfields structs use the niche of their fields.
+    assert_eq!(OptionalModes::NICHE, Some(0b00_01_00));
+    assert_eq!(FlagAndMode::NICHE, Some(0b110));
+    assert_values::<Option<FlagAndMode>>(0, 3, 0b111);
+    assert_eq!(None::<FlagAndMode>.into_raw(), 0b110);
+    let flag_and_mode = FlagAndMode::new(true, ThreeModes::C);
+    assert_eq!(Option::<FlagAndMode>::try_from_raw(0b101), Some(Some(flag_and_mode)));
+    assert_eq!(Option::<FlagAndMode>::try_from_raw(0b110), Some(None));
+}
+
+#[test]
+fn arrays() {
+    assert_values::<[ShiftedModes; 3]>(0, 6, 0b111111);
+    assert_eq!([ShiftedModes::A, ShiftedModes::B, ShiftedModes::B].into_raw(), 0b10_10_01);
+    assert_eq!(
+        <[ShiftedModes; 3]>::try_from_raw(0b01_10_01),
+        Some([ShiftedModes::A, ShiftedModes::B, ShiftedModes::A]),
+    );
+    assert_eq!(<[ShiftedModes; 3]>::try_from_raw(0b01_00_01), None);
+    assert_eq!(<[ShiftedModes; 3]>::try_from_raw(0b1_01_01_01), None);
+    assert_eq!(<[ShiftedModes; 3]>::NICHE, Some(0));
+    assert_eq!(<[bool; 2]>::NICHE, None);
+
+    assert_values::<[bool; 0]>(0, 0, 0);
+    assert_eq!(<[bool; 0]>::try_from_raw(0), Some([]));
+}
+
+#[test]
+fn tuples() {
+    use superbitty::u3;
+
+    type Pair = (ShiftedModes, u3);
+    assert_values::<Pair>(0, 5, 0b11111);
+    let pair = (ShiftedModes::B, u3::new(0b101).unwrap());
+    assert_eq!(pair.into_raw(), 0b1_0110);
+    assert_eq!(Pair::try_from_raw(0b1_0110), Some(pair));
+    assert_eq!(Pair::try_from_raw(0b1_0100), None);
+    assert_eq!(Pair::try_from_raw(0b10_0001), None);
+    assert_eq!(Pair::NICHE, Some(0));
+
+    // A zero-width element after 128 bits.
+    type Wide = (u64, u64, [u8; 0]);
+    let wide = (u64::MAX, 1, []);
+    assert_eq!(wide.into_raw(), 1 << 64 | u64::MAX as u128);
+    assert_eq!(Wide::try_from_raw(1 << 64 | u64::MAX as u128), Some(wide));
+
+    assert_eq!(<(bool, ThreeModes)>::NICHE, Some(0b110));
+    assert_eq!(<(bool,)>::NICHE, None);
+    assert_eq!((true, false, true).into_raw(), 0b101);
+}
+
+#[test]
+fn core_types() {
+    use std::num::{NonZeroU16, NonZeroU8};
+
+    assert_values::<u8>(0, 8, 0xFF);
+    assert_values::<u64>(0, 64, u64::MAX as u128);
+    assert_eq!(u16::try_from_raw(0xFFFF), Some(0xFFFF));
+    assert_eq!(u16::try_from_raw(0x1_0000), None);
+
+    assert_values::<NonZeroU8>(0, 8, 0xFF);
+    assert_eq!(NonZeroU8::try_from_raw(0), None);
+    assert_eq!(NonZeroU8::try_from_raw(7), NonZeroU8::new(7));
+    assert_eq!(NonZeroU8::try_from_raw(0x100), None);
+    assert_values::<Option<NonZeroU16>>(0, 16, 0xFFFF);
+    assert_eq!(None::<NonZeroU16>.into_raw(), 0);
+
+    assert_values::<char>(0, 21, 0x1F_FFFF);
+    assert_eq!('\u{10FFFF}'.into_raw(), 0x10_FFFF);
+    assert_eq!(char::try_from_raw('a' as u128), Some('a'));
+    assert_eq!(char::try_from_raw(0xD800), None);
+    assert_eq!(char::try_from_raw(0x11_0000), None);
+    assert_eq!(char::try_from_raw(u128::MAX), None);
+    assert_values::<Option<char>>(0, 21, 0x1F_FFFF);
+    assert_eq!(Option::<char>::try_from_raw(0xD800), Some(None));
+}
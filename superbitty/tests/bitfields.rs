@@ -41,6 +41,13 @@ fn new_and_set() {
     assert_eq!(instance.enum_b(), EnumB::C);
 }
 
+#[test]
+fn with() {
+    let instance = Bitfields::default().with_enum_a(EnumA::B).with_enum_b(EnumB::A);
+    assert_eq!(instance, Bitfields::new(EnumA::B, EnumB::A));
+    assert_eq!(instance.with_enum_a(EnumA::A), Bitfields::new(EnumA::A, EnumB::A));
+}
+
 #[test]
 fn default() {
     let instance = Bitfields::default();
@@ -55,6 +62,7 @@ fn debug() {
 }
 
 #[test]
+#[allow(clippy::clone_on_copy)]
 fn clone_copy() {
     let original = Bitfields::new(EnumA::A, EnumB::C);
     let clone = original.clone();
@@ -133,6 +141,7 @@ bitfields! {
 }
 
 #[test]
+#[allow(clippy::clone_on_copy, clippy::nonminimal_bool)]
 fn empty() {
     _ = Empty::new().clone();
     _ = Empty::default();
@@ -141,3 +150,786 @@ fn empty() {
     assert!(Empty::default() >= Empty::new());
     assert_eq!(Empty::new().partial_cmp(&Empty::new()), Some(Ordering::Equal));
 }
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct WithBools : u8 {
+        pub a: bool,
+        pub e: EnumB,
+        pub b: bool,
+    }
+}
+
+#[test]
+fn bools() {
+    let mut instance = WithBools::new(true, EnumB::B, false);
+    assert!(instance.a());
+    assert_eq!(instance.e(), EnumB::B);
+    assert!(!instance.b());
+    assert_eq!(instance.0.raw(), 0b0011);
+
+    instance.set_b(true);
+    instance.set_a(false);
+    assert!(!instance.a());
+    assert_eq!(instance.e(), EnumB::B);
+    assert!(instance.b());
+    assert_eq!(instance.0.raw(), 0b1010);
+
+    assert_eq!(format!("{instance:?}"), "WithBools { a: false, e: B, b: true }");
+    assert_eq!(instance, WithBools::new(false, EnumB::B, true));
+    assert_ne!(instance, WithBools::new(true, EnumB::B, true));
+}
+
+#[test]
+fn from_raw() {
+    for a in [EnumA::A, EnumA::B] {
+        for b in [EnumB::A, EnumB::B, EnumB::C] {
+            let instance = Bitfields::new(a, b);
+            assert_eq!(Bitfields::from_raw(instance.0.raw()), Ok(instance));
+            // SAFETY: The raw value came from a valid instance.
+            assert_eq!(unsafe { Bitfields::from_raw_unchecked(instance.0.raw()) }, instance);
+        }
+    }
+
+    // `EnumB` has no variant with discriminant 3.
+    assert!(Bitfields::from_raw(0b110).is_err());
+    // Bits outside of the fields must be zero.
+    assert!(Bitfields::from_raw(0b1000).is_err());
+
+    assert_eq!(WithBools::from_raw(0b1011), Ok(WithBools::new(true, EnumB::B, true)));
+    assert_eq!(WithBools::try_from(0b1011), Ok(WithBools::new(true, EnumB::B, true)));
+    assert!(WithBools::from_raw(0b0111).is_err());
+    assert!(WithBools::from_raw(0b1_0000).is_err());
+    assert!(WithBools::try_from(0b1_0000).is_err());
+    assert_eq!(Empty::from_raw(0), Ok(Empty::new()));
+    assert!(Empty::from_raw(1).is_err());
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Mode : u8 {
+        a: EnumA,
+        b: EnumB,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ControlWord : u32 {
+        pub enabled: bool,
+        pub mode: Mode,
+        pub other_mode: Mode,
+        empty: Empty,
+        pub flags: WithBools,
+    }
+}
+
+bitfields! {
+    struct NotCopy : u8 {
+        a: EnumA,
+    }
+}
+
+#[test]
+fn nested() {
+    // Structs that are not `Copy` don't implement `BitFieldCompatible`, but still compile.
+    assert_eq!(NotCopy::new(EnumA::B).a(), EnumA::B);
+
+    assert_eq!(<Mode as BitFieldCompatible>::BITS_LEN, 3);
+    assert_eq!(<Empty as BitFieldCompatible>::BITS_LEN, 0);
+    assert_eq!(<ControlWord as BitFieldCompatible>::BITS_LEN, 11);
+
+    let mut control = ControlWord::new(
+        true,
+        Mode::new(EnumA::B, EnumB::C),
+        Mode::new(EnumA::A, EnumB::B),
+        Empty::new(),
+        WithBools::new(false, EnumB::A, true),
+    );
+    assert_eq!(control.0.raw(), 0b100_0010_1011);
+    assert_eq!(control.mode(), Mode::new(EnumA::B, EnumB::C));
+    assert_eq!(control.other_mode().b(), EnumB::B);
+
+    let mut mode = control.mode();
+    mode.set_b(EnumB::A);
+    control.set_mode(mode);
+    assert_eq!(control.mode(), Mode::new(EnumA::B, EnumB::A));
+    assert_eq!(control.other_mode(), Mode::new(EnumA::A, EnumB::B));
+    assert!(control.enabled());
+    assert_eq!(control.flags(), WithBools::new(false, EnumB::A, true));
+
+    assert_eq!(ControlWord::from_raw(control.0.raw()), Ok(control));
+    // The inner `Mode` has an invalid `EnumB`.
+    assert!(ControlWord::from_raw(0b100_0010_1111).is_err());
+    assert!(Mode::is_valid_raw(0b101));
+    assert!(!Mode::is_valid_raw(0b111));
+    assert!(!Mode::is_valid_raw(0b1000));
+    assert!(!Mode::is_valid_raw(0x100));
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Channels : u64 {
+        enabled: bool,
+        selectors: [superbitty::u4; 8],
+        modes: [EnumB; 3],
+    }
+}
+
+#[test]
+fn arrays() {
+    use superbitty::u4;
+
+    let selectors = [1, 2, 3, 4, 5, 6, 7, 8].map(|v| u4::new(v).unwrap());
+    let mut channels = Channels::new(true, selectors, [EnumB::A, EnumB::B, EnumB::C]);
+    assert_eq!(<[u4; 8] as BitFieldCompatible>::BITS_LEN, 32);
+    assert_eq!(channels.0.raw(), 0b10_01_00 << 33 | 0x8765_4321 << 1 | 1);
+    assert_eq!(channels.selectors(2).value(), 3);
+    assert_eq!(channels.modes(1), EnumB::B);
+    assert_eq!(channels.selectors_array(), selectors);
+
+    channels.set_selectors(7, u4::MAX);
+    channels.set_modes(0, EnumB::C);
+    assert_eq!(channels.selectors(7), u4::MAX);
+    assert_eq!(channels.selectors(6).value(), 7);
+    assert_eq!(channels.modes_array(), [EnumB::C, EnumB::B, EnumB::C]);
+    assert!(channels.enabled());
+
+    channels.set_modes_array([EnumB::A; 3]);
+    channels.set_selectors_array([u4::MIN; 8]);
+    assert_eq!(channels, Channels::new(true, [u4::MIN; 8], [EnumB::A; 3]));
+    assert_eq!(
+        channels.with_selectors(0, u4::MAX).with_modes_array([EnumB::B; 3]),
+        Channels::new(
+            true,
+            [u4::MAX, u4::MIN, u4::MIN, u4::MIN, u4::MIN, u4::MIN, u4::MIN, u4::MIN],
+            [EnumB::B; 3]
+        ),
+    );
+    assert_eq!(Channels::from_raw(channels.0.raw()), Ok(channels));
+    // `EnumB` has no variant with discriminant 3.
+    assert!(Channels::from_raw(0b11 << 35).is_err());
+}
+
+#[test]
+#[should_panic = "index out of bounds: the len is 3 but the index is 3"]
+fn array_index_out_of_bounds() {
+    let channels = Channels::new(false, Default::default(), [EnumB::A; 3]);
+    channels.modes(3);
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[bitfields(const)]
+    struct WideArray : [u8; 24] {
+        halves: [u64; 2],
+        flag: bool,
+    }
+}
+
+#[test]
+fn wide_arrays() {
+    let wide = WideArray::new([u64::MAX, 0x0123_4567_89AB_CDEF], true);
+    assert_eq!(<[u64; 2] as BitFieldCompatible>::BITS_LEN, 128);
+    assert_eq!(wide.halves(1), 0x0123_4567_89AB_CDEF);
+    assert_eq!(wide.halves_array(), [u64::MAX, 0x0123_4567_89AB_CDEF]);
+    assert_eq!(WideArray::from_raw(wide.0.raw()), Ok(wide));
+    assert!(<[u64; 2] as BitFieldCompatible>::is_valid_raw(u128::MAX));
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct WithTuple : u8 {
+        flag: bool,
+        pair: (EnumA, superbitty::u3),
+    }
+}
+
+#[test]
+fn tuples() {
+    use superbitty::u3;
+
+    let mut with_tuple = WithTuple::new(true, (EnumA::B, u3::new(0b110).unwrap()));
+    assert_eq!(with_tuple.0.raw(), 0b1_1011); // `u3`, `EnumA` and `bool`.
+    assert_eq!(with_tuple.pair(), (EnumA::B, u3::new(0b110).unwrap()));
+    with_tuple.set_pair((EnumA::A, u3::MAX));
+    assert_eq!(with_tuple.pair().1, u3::MAX);
+    assert!(with_tuple.flag());
+    assert_eq!(WithTuple::from_raw(with_tuple.0.raw()), Ok(with_tuple));
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Packet : u64 {
+        kind: u8,
+        length: std::num::NonZeroU16,
+        letter: char,
+    }
+}
+
+#[test]
+fn core_types() {
+    let length = std::num::NonZeroU16::new(300).unwrap();
+    let mut packet = Packet::new(0xAB, length, 'z');
+    assert_eq!(packet.0.raw(), ('z' as u64) << 24 | 300 << 8 | 0xAB);
+    assert_eq!(packet.kind(), 0xAB);
+    assert_eq!(packet.length(), length);
+    packet.set_letter('\u{1F980}');
+    assert_eq!(packet.letter(), '\u{1F980}');
+    assert_eq!(Packet::from_raw(packet.0.raw()), Ok(packet));
+    // A zero length.
+    assert!(Packet::from_raw(0xAB).is_err());
+    // A surrogate.
+    assert!(Packet::from_raw(0xD800 << 24 | 1 << 8).is_err());
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Status : u16 {
+        #[bit(0)]
+        enabled: bool,
+        #[bits(4..8)]
+        mode: superbitty::u4,
+        // Packed right after `mode`.
+        level: superbitty::u3,
+        #[bits(12..=15)]
+        channel: superbitty::u4,
+    }
+}
+
+#[test]
+fn explicit_positions() {
+    use superbitty::{u3, u4};
+
+    let mut status = Status::new(true, u4::new(0b1010).unwrap(), u3::new(0b101).unwrap(), u4::MAX);
+    assert_eq!(status.0.raw(), 0b1111_0101_1010_0001);
+    assert_eq!(status.mode(), u4::new(0b1010).unwrap());
+    assert!(status.enabled());
+    assert_eq!(status.level(), u3::new(0b101).unwrap());
+    assert_eq!(status.channel(), u4::MAX);
+
+    status.set_enabled(false);
+    status.set_mode(u4::MIN);
+    assert_eq!(status.0.raw(), 0b1111_0101_0000_0000);
+    assert_eq!(status.channel(), u4::MAX);
+    assert_eq!(<Status as BitFieldCompatible>::BITS_LEN, 16);
+
+    // Bits that don't belong to any field must be zero.
+    assert_eq!(Status::from_raw(status.0.raw()), Ok(status));
+    assert!(Status::from_raw(0b10).is_err());
+    assert!(Status::from_raw(1 << 11).is_err());
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct WithReserved : u16 {
+        low: EnumA,
+        _: 3,
+        /// Kept as is, whatever the hardware puts there.
+        #[reserved(keep)]
+        _: 4,
+        high: EnumB,
+        #[bits(14..16)]
+        #[reserved(zero)]
+        _: 2,
+    }
+}
+
+#[test]
+fn reserved() {
+    let mut instance = WithReserved::new(EnumA::B, EnumB::C);
+    assert_eq!(instance.0.raw(), 0b10_0000_0001);
+    assert_eq!(<WithReserved as BitFieldCompatible>::BITS_LEN, 16);
+    assert_eq!(format!("{instance:?}"), "WithReserved { low: B, high: C }");
+
+    // Kept reserved bits are accepted and preserved by setters.
+    let mut kept = WithReserved::from_raw(0b10_1011_0001).unwrap();
+    assert_eq!(kept, instance);
+    kept.set_low(EnumA::A);
+    assert_eq!(kept.0.raw(), 0b10_1011_0000);
+    instance.set_low(EnumA::A);
+    assert_eq!(kept, instance);
+
+    // Zeroed reserved bits must be zero.
+    assert!(WithReserved::from_raw(0b10_0000_0101).is_err());
+    assert!(WithReserved::from_raw(1 << 15 | 0b10_0000_0001).is_err());
+}
+
+bitfields! {
+    /// Like the first byte of an IPv4 header.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[bitfields(order = msb0)]
+    struct VersionAndLength : u8 {
+        version: superbitty::u4,
+        header_length: superbitty::u4,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[bitfields(order = msb0)]
+    struct Flags : u16 {
+        evil: bool,
+        dont_fragment: bool,
+        #[bits(4..8)]
+        kind: superbitty::u4,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[bitfields(order = msb0)]
+    struct Nibbles : u16 {
+        tag: superbitty::u4,
+        nibbles: [superbitty::u4; 3],
+    }
+}
+
+#[test]
+fn msb0() {
+    use superbitty::u4;
+
+    let mut value = VersionAndLength::new(u4::new(4).unwrap(), u4::new(5).unwrap());
+    assert_eq!(value.0.raw(), 0x45);
+    value.set_header_length(u4::new(0xF).unwrap());
+    assert_eq!(value.version(), u4::new(4).unwrap());
+    assert_eq!(value.0.raw(), 0x4F);
+
+    let flags = Flags::new(false, true, u4::new(0b1001).unwrap());
+    assert_eq!(flags.0.raw(), 0b0100_1001_0000_0000);
+    assert_eq!(flags.kind(), u4::new(0b1001).unwrap());
+    assert_eq!(Flags::from_raw(flags.0.raw()), Ok(flags));
+    assert!(Flags::from_raw(1).is_err());
+    assert_eq!(<Flags as BitFieldCompatible>::BITS_LEN, 16);
+
+    // The elements of an array are still packed from the least significant end of the field.
+    let one = u4::new(1).unwrap();
+    let nibbles =
+        Nibbles::new(u4::new(0xA).unwrap(), [one, u4::new(2).unwrap(), u4::new(3).unwrap()]);
+    assert_eq!(nibbles.0.raw(), 0xA_3_2_1);
+    assert_eq!(nibbles.nibbles(0), one);
+    assert_eq!(Nibbles::new(u4::new(0).unwrap(), [one, u4::MIN, u4::MIN]).0.raw(), 1);
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Descriptor : [u8; 24] {
+        kind: superbitty::u4,
+        // Crosses from the first byte to the third.
+        length: superbitty::u12,
+        address: u64,
+        #[bits(100..160)]
+        tag: superbitty::u60,
+        last: bool,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Words : [u32; 2] {
+        low: superbitty::u20,
+        // Crosses the word boundary.
+        middle: superbitty::u24,
+        high: superbitty::u20,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[bitfields(order = msb0, const)]
+    struct Wide : [u64; 3] {
+        // A zero-width element after 128 bits.
+        first: (u64, u64, [u8; 0]),
+        second: superbitty::u7,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct NestsWords : u128 {
+        flag: bool,
+        words: Words,
+    }
+}
+
+#[test]
+fn array_base_types() {
+    use superbitty::{u12, u20, u24, u4, u60, u7};
+
+    let tag = u60::new(0xABC_DEF0_1234_5678).unwrap();
+    let mut descriptor =
+        Descriptor::new(u4::new(0x3).unwrap(), u12::new(0xFED).unwrap(), u64::MAX - 1, tag, true);
+    let raw = descriptor.0.raw();
+    assert_eq!(raw[..3], [0xD3, 0xFE, 0xFE]);
+    assert_eq!(raw[3..10], [0xFF; 7]);
+    assert_eq!(raw[12], 0x80);
+    assert_eq!(raw[19..21], [0xAB, 0x01]);
+    assert_eq!(descriptor.length(), u12::new(0xFED).unwrap());
+    assert_eq!(descriptor.address(), u64::MAX - 1);
+    assert_eq!(descriptor.tag(), tag);
+    assert!(descriptor.last());
+    descriptor.set_length(u12::MIN);
+    assert_eq!(descriptor.0.raw()[..3], [0x03, 0x00, 0xFE]);
+    assert_eq!(descriptor.kind(), u4::new(0x3).unwrap());
+    assert_eq!(Descriptor::from_raw(descriptor.0.raw()), Ok(descriptor));
+    // A bit between `address` and `tag`.
+    let mut invalid = descriptor.0.raw();
+    invalid[10] = 1;
+    assert!(Descriptor::from_raw(invalid).is_err());
+
+    let words = Words::new(u20::MAX, u24::new(0x12_3456).unwrap(), u20::new(0xA_BCDE).unwrap());
+    assert_eq!(words.0.raw(), [0x456F_FFFF, 0xABCD_E123]);
+    assert_eq!(words.middle(), u24::new(0x12_3456).unwrap());
+    assert_eq!(<Words as BitFieldCompatible>::BITS_LEN, 64);
+
+    let nests = NestsWords::new(true, words);
+    assert_eq!(nests.words(), words);
+    assert_eq!(nests.0.raw() >> 1, 0xABCD_E123_456F_FFFF);
+
+    let wide = Wide::new((u64::MAX, u64::MAX, []), u7::new(0b101_0101).unwrap());
+    assert_eq!(wide.0.raw(), [0xAA00_0000_0000_0000, u64::MAX, u64::MAX]);
+    assert_eq!(wide.second().value(), 0b101_0101);
+    assert_eq!(Wide::from_raw(wide.0.raw()), Ok(wide));
+    assert!(<(u64, u64) as BitFieldCompatible>::is_valid_raw(u128::MAX));
+}
+
+#[test]
+fn bytes() {
+    use superbitty::{u20, u24};
+
+    let status = Status::from_raw(0x15A1).unwrap();
+    assert_eq!(status.to_le_bytes(), [0xA1, 0x15]);
+    assert_eq!(status.to_be_bytes(), [0x15, 0xA1]);
+    assert_eq!(status.to_ne_bytes(), 0x15A1u16.to_ne_bytes());
+    assert_eq!(Status::from_le_bytes([0xA1, 0x15]), Ok(status));
+    assert_eq!(Status::from_be_bytes([0x15, 0xA1]), Ok(status));
+    assert_eq!(Status::from_ne_bytes(0x15A1u16.to_ne_bytes()), Ok(status));
+    assert!(Status::from_be_bytes([0xA1, 0x15]).is_err());
+
+    let words = Words::new(u20::MAX, u24::new(0x12_3456).unwrap(), u20::new(0xA_BCDE).unwrap());
+    let le = [0xFF, 0xFF, 0x6F, 0x45, 0x23, 0xE1, 0xCD, 0xAB];
+    assert_eq!(words.to_le_bytes(), le);
+    assert_eq!(words.to_be_bytes(), 0xABCD_E123_456F_FFFFu64.to_be_bytes());
+    assert_eq!(Words::from_le_bytes(le), Ok(words));
+    assert_eq!(Words::from_be_bytes(0xABCD_E123_456F_FFFFu64.to_be_bytes()), Ok(words));
+}
+
+#[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+enum Addr {
+    Local = 0x01,
+    Broadcast = 0xFF,
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Target : u16 {
+        enabled: bool,
+        union {
+            nibbles {
+                low: superbitty::u4,
+                high: superbitty::u4,
+            },
+            #[primary]
+            addr {
+                addr: Addr,
+            },
+        },
+        mode: superbitty::u3,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Narrow : u8 {
+        union {
+            small {
+                small: superbitty::u4,
+            },
+            big {
+                big: u8,
+            },
+        },
+    }
+}
+
+#[test]
+fn views() {
+    use superbitty::{u3, u4};
+
+    // The constructor and the derives use the primary view.
+    let mut target = Target::new(true, Addr::Local, u3::new(5).unwrap());
+    assert_eq!(target.0.raw(), 0b101 << 9 | 0x01 << 1 | 1);
+    assert_eq!(format!("{target:?}"), "Target { enabled: true, addr: Local, mode: 5 }");
+    assert_eq!(target.addr(), Addr::Local);
+    assert!(target.is_addr_valid());
+    assert!(target.is_nibbles_valid());
+    assert_eq!(target.low(), Some(u4::new(1).unwrap()));
+    assert_eq!(target.high(), Some(u4::MIN));
+
+    // Writing through another view must keep the primary view valid.
+    assert!(target.set_high(u4::new(3).unwrap()).is_err());
+    assert_eq!(target.addr(), Addr::Local);
+    assert!(target.set_low(u4::MAX).is_err());
+    target.set_addr(Addr::Broadcast);
+    assert_eq!(target.high(), Some(u4::MAX));
+    assert_eq!(target.set_low(u4::MAX), Ok(()));
+    assert_eq!(target.mode(), u3::new(5).unwrap());
+    assert_eq!(target.with_low(u4::MAX), Ok(target));
+    assert!(target.with_high(u4::MIN).is_err());
+
+    assert_eq!(Target::from_raw(target.0.raw()), Ok(target));
+    // Valid for `nibbles`, but not for the primary view.
+    assert!(Target::from_raw(0x23 << 1).is_err());
+
+    // The bits the primary view doesn't use belong to the other views.
+    let mut narrow = Narrow::from_raw(0xA5).unwrap();
+    assert_eq!(narrow.small(), u4::new(5).unwrap());
+    assert_eq!(narrow.big(), Some(0xA5));
+    assert_eq!(narrow.set_big(0x3C), Ok(()));
+    assert_eq!(narrow.small(), u4::new(0xC).unwrap());
+    assert_eq!(<Narrow as BitFieldCompatible>::BITS_LEN, 8);
+}
+
+#[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i8)]
+enum Signed {
+    Minus = -1,
+    Zero,
+    Plus,
+}
+
+#[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+#[bit_field(compact)]
+enum Sparse {
+    A = 10,
+    B = 20,
+}
+
+#[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+#[bit_field(bits = 5)]
+struct Small(u8);
+
+#[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+struct Wrapped(superbitty::u3);
+
+#[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum Open {
+    Known = 1,
+    #[bit_field(catch_all)]
+    Other(superbitty::u4),
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[bitfields(const)]
+    struct ConstInner : u8 {
+        a: EnumA,
+        b: superbitty::u3,
+    }
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[bitfields(const)]
+    struct ConstTarget : u8 {
+        union {
+            #[primary]
+            addr {
+                addr: Addr,
+            },
+            nibbles {
+                low: superbitty::u4,
+                high: superbitty::u4,
+            },
+        },
+    }
+}
+
+const CONST_TARGET: ConstTarget = ConstTarget::new(Addr::Local).with_addr(Addr::Broadcast);
+
+// The same fields, converted with and without `const`.
+macro_rules! all_kinds_of_fields {
+    ( $( #[$attr:meta] )* $name:ident ) => {
+        bitfields! {
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            $( #[$attr] )*
+            struct $name : u128 {
+                flag: bool,
+                enums: (Signed, Sparse, Open),
+                newtypes: (Small, Wrapped, superbitty::i5),
+                inner: ConstInner,
+                core: (char, std::num::NonZeroU16, u8),
+                options: (Option<EnumB>, Option<bool>),
+                nibbles: [superbitty::u4; 3],
+            }
+        }
+    };
+}
+
+all_kinds_of_fields!(
+    #[bitfields(const)]
+    ConstFields
+);
+all_kinds_of_fields!(RuntimeFields);
+
+const CONST_FIELDS: ConstFields = ConstFields::new(
+    true,
+    (Signed::Minus, Sparse::B, Open::Other(superbitty::u4::new_wrapping(9))),
+    (Small(17), Wrapped(superbitty::u3::MAX), superbitty::i5::new_wrapping(-7)),
+    ConstInner::new(EnumA::B, superbitty::u3::new_wrapping(5)),
+    (
+        '\u{1F980}',
+        match std::num::NonZeroU16::new(300) {
+            Some(length) => length,
+            None => unreachable!(),
+        },
+        0xAB,
+    ),
+    (None, Some(false)),
+    [superbitty::u4::new_wrapping(1), superbitty::u4::new_wrapping(2), superbitty::u4::MAX],
+);
+
+const CONST_READ: (Signed, Option<EnumB>, [superbitty::u4; 3], superbitty::u4) = (
+    CONST_FIELDS.enums().0,
+    CONST_FIELDS.options().0,
+    CONST_FIELDS.nibbles_array(),
+    CONST_FIELDS.nibbles(1),
+);
+
+static CONST_MODIFIED: ConstFields = {
+    let mut fields = CONST_FIELDS.with_flag(false).with_nibbles(0, superbitty::u4::MIN);
+    fields.set_options((Some(EnumB::C), None));
+    fields.set_enums((Signed::Plus, Sparse::A, Open::Known));
+    fields
+};
+
+#[test]
+fn const_fns() {
+    use superbitty::{u3, u4};
+
+    let runtime = RuntimeFields::new(
+        CONST_FIELDS.flag(),
+        CONST_FIELDS.enums(),
+        CONST_FIELDS.newtypes(),
+        CONST_FIELDS.inner(),
+        CONST_FIELDS.core(),
+        CONST_FIELDS.options(),
+        CONST_FIELDS.nibbles_array(),
+    );
+    assert_eq!(CONST_FIELDS.0.raw(), runtime.0.raw());
+    assert_eq!(ConstFields::from_raw(CONST_FIELDS.0.raw()), Ok(CONST_FIELDS));
+    assert_eq!(CONST_FIELDS.enums().2, Open::Other(u4::new(9).unwrap()));
+    assert_eq!(CONST_FIELDS.newtypes().2.value(), -7);
+    assert_eq!(CONST_FIELDS.inner(), ConstInner::new(EnumA::B, u3::new(5).unwrap()));
+    assert_eq!(CONST_FIELDS.core().0, '\u{1F980}');
+    assert_eq!(
+        CONST_READ,
+        (
+            Signed::Minus,
+            None,
+            [u4::new(1).unwrap(), u4::new(2).unwrap(), u4::MAX],
+            u4::new(2).unwrap()
+        ),
+    );
+
+    assert!(!CONST_MODIFIED.flag());
+    assert_eq!(CONST_MODIFIED.nibbles_array(), [u4::MIN, u4::new(2).unwrap(), u4::MAX]);
+    assert_eq!(CONST_MODIFIED.options(), (Some(EnumB::C), None));
+    assert_eq!(CONST_MODIFIED.enums(), (Signed::Plus, Sparse::A, Open::Known));
+    let runtime = runtime
+        .with_flag(false)
+        .with_nibbles(0, u4::MIN)
+        .with_options((Some(EnumB::C), None))
+        .with_enums((Signed::Plus, Sparse::A, Open::Known));
+    assert_eq!(CONST_MODIFIED.0.raw(), runtime.0.raw());
+
+    // Only the primary view is `const`, the other views are accessed at runtime.
+    assert_eq!(CONST_TARGET.addr(), Addr::Broadcast);
+    assert_eq!((CONST_TARGET.low(), CONST_TARGET.high()), (Some(u4::MAX), Some(u4::MAX)));
+    assert!(CONST_TARGET.with_low(u4::MIN).is_err());
+    assert_eq!(
+        CONST_TARGET.with_addr(Addr::Local).with_low(u4::new(1).unwrap()),
+        Ok(ConstTarget::new(Addr::Local)),
+    );
+}
+
+// The generated code must not be affected by types shadowing the primitives.
+mod shadowed_primitives {
+    #![allow(non_camel_case_types, dead_code)]
+
+    use superbitty::{bitfields, u4, BitFieldCompatible};
+
+    struct u8;
+    struct u32;
+    struct u128;
+    struct usize;
+    struct bool;
+    struct i128;
+
+    #[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(i8)]
+    pub enum Signed {
+        Minus = -1,
+        Plus = 1,
+    }
+
+    #[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_field(bits = 3)]
+    pub enum Pinned {
+        A,
+        B,
+    }
+
+    #[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+    #[bit_field(compact)]
+    pub enum Compact {
+        A = 10,
+        B = 20,
+    }
+
+    #[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum CatchAll {
+        Zero,
+        #[bit_field(catch_all)]
+        Other(::core::primitive::u8),
+    }
+
+    #[derive(BitFieldCompatible, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Newtype(u4);
+
+    bitfields! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[bitfields(const)]
+        pub struct Shadowed : u64 {
+            pub flag: ::core::primitive::bool,
+            pub nibbles: [u4; 2],
+            _: 3,
+            pub byte: ::core::primitive::u8,
+            pub signed: Signed,
+            pub enums: (Pinned, Compact),
+            pub catch_all: CatchAll,
+            pub newtype: Newtype,
+        }
+    }
+
+    #[test]
+    fn shadowed() {
+        let shadowed = Shadowed::new(
+            true,
+            [u4::MAX, u4::MIN],
+            0,
+            Signed::Minus,
+            (Pinned::B, Compact::B),
+            CatchAll::Other(7),
+            Newtype(u4::MAX),
+        );
+        assert_eq!(Shadowed::from_raw(shadowed.0.raw()), Ok(shadowed));
+        assert_eq!(shadowed.with_nibbles(1, u4::MAX).0.raw() & 0x1FF, 0xFF << 1 | 1);
+        assert_eq!(Shadowed::from_le_bytes(shadowed.to_le_bytes()), Ok(shadowed));
+        assert_eq!(shadowed.signed(), Signed::Minus);
+        assert_eq!(shadowed.catch_all(), CatchAll::Other(7));
+    }
+}
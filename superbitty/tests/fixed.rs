@@ -0,0 +1,157 @@
+use superbitty::{bitfields, BitFieldCompatible, Fixed, Rounding, UFixed};
+
+type Q4_8 = Fixed<4, 8>;
+type UQ2_2 = UFixed<2, 2>;
+
+#[test]
+fn constants() {
+    assert_eq!(Q4_8::BITS, 12);
+    assert_eq!(Q4_8::MIN.to_f64(), -8.0);
+    assert_eq!(Q4_8::MAX.to_f64(), 8.0 - 1.0 / 256.0);
+    assert_eq!(Q4_8::DELTA.to_f64(), 1.0 / 256.0);
+    assert_eq!(UQ2_2::MIN.to_f64(), 0.0);
+    assert_eq!(UQ2_2::MAX.to_f64(), 3.75);
+    assert_eq!(<Q4_8 as BitFieldCompatible>::BITS_LEN, 12);
+    assert_eq!(<UFixed<0, 8> as BitFieldCompatible>::BITS_LEN, 8);
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(Q4_8::from_bits(-0x800), Some(Q4_8::MIN));
+    assert_eq!(Q4_8::from_bits(0x800), None);
+    assert_eq!(UQ2_2::from_bits(0b1011).map(UQ2_2::to_f64), Some(2.75));
+    assert_eq!(Q4_8::from_f64(-1.5, Rounding::Nearest).map(Q4_8::to_bits), Some(-0x180));
+    assert_eq!(UQ2_2::from_f32(1.25, Rounding::Nearest).map(UQ2_2::to_f32), Some(1.25));
+    // Rounding to `f64` first would give a tie that rounds down to `2^54`.
+    let above_tie: i128 = (1 << 54) + (1 << 30) + 1;
+    let expected = ((1u64 << 54) + (1 << 31)) as f32;
+    assert_eq!(
+        UFixed::<56, 8>::from_bits((above_tie as u128) << 8).map(UFixed::to_f32),
+        Some(expected)
+    );
+    assert_eq!(Fixed::<57, 8>::from_bits(-above_tie << 8).map(Fixed::to_f32), Some(-expected));
+    assert_eq!(UFixed::<1, 127>::DELTA.to_f32(), f32::MIN_POSITIVE / 2.0);
+
+    assert_eq!(UQ2_2::from_f64(4.0, Rounding::Nearest), None);
+    assert_eq!(UQ2_2::from_f64(3.8, Rounding::Nearest), Some(UQ2_2::MAX));
+    assert_eq!(UQ2_2::from_f64(3.9, Rounding::Nearest), None);
+    assert_eq!(UQ2_2::from_f64(-0.1, Rounding::Nearest), Some(UQ2_2::MIN));
+    assert_eq!(UQ2_2::from_f64(-0.2, Rounding::Nearest), None);
+    assert_eq!(UQ2_2::from_f64(f64::NAN, Rounding::Nearest), None);
+    assert_eq!(UQ2_2::from_f64(f64::INFINITY, Rounding::Nearest), None);
+    assert_eq!(Q4_8::from_f64(1e300, Rounding::Nearest), None);
+}
+
+#[test]
+fn rounding() {
+    let round = |value, rounding| UQ2_2::from_f64(value, rounding).unwrap().to_f64();
+    assert_eq!(round(1.1, Rounding::Nearest), 1.0);
+    assert_eq!(round(1.125, Rounding::Nearest), 1.25);
+    assert_eq!(round(1.1, Rounding::Floor), 1.0);
+    assert_eq!(round(1.1, Rounding::Ceil), 1.25);
+    assert_eq!(round(1.2, Rounding::TowardZero), 1.0);
+
+    let round = |value, rounding| Q4_8::from_f64(value, rounding).unwrap().to_bits();
+    assert_eq!(round(-1.0 / 512.0, Rounding::Nearest), -1);
+    assert_eq!(round(-1.0 / 1024.0, Rounding::Nearest), 0);
+    assert_eq!(round(-1.0 / 1024.0, Rounding::Floor), -1);
+    assert_eq!(round(-1.0 / 1024.0, Rounding::Ceil), 0);
+    assert_eq!(round(-1.0 / 1024.0, Rounding::TowardZero), 0);
+}
+
+#[test]
+fn arithmetic() {
+    let q = |value| Q4_8::from_f64(value, Rounding::Nearest).unwrap();
+    assert_eq!(q(1.5) + q(2.25), q(3.75));
+    assert_eq!(q(1.5) - q(2.25), q(-0.75));
+    assert_eq!(q(1.5) * q(-2.5), q(-3.75));
+    assert_eq!(q(3.0) / q(-2.0), q(-1.5));
+    assert_eq!(-q(2.0), q(-2.0));
+
+    // Truncated toward zero.
+    assert_eq!(Q4_8::DELTA * q(0.5), q(0.0));
+    assert_eq!((-Q4_8::DELTA) * q(0.5), q(0.0));
+    assert_eq!(Q4_8::DELTA / q(2.0), q(0.0));
+
+    assert_eq!(Q4_8::MAX.checked_add(Q4_8::DELTA), None);
+    assert_eq!(Q4_8::MIN.checked_sub(Q4_8::DELTA), None);
+    assert_eq!(q(4.0).checked_mul(q(2.0)), None);
+    assert_eq!(q(1.0).checked_div(q(0.0)), None);
+    assert_eq!(q(4.0).checked_div(q(0.5)), None);
+    assert_eq!(Q4_8::MIN.checked_neg(), None);
+    assert_eq!(Q4_8::MAX.saturating_add(q(1.0)), Q4_8::MAX);
+    assert_eq!(Q4_8::MIN.saturating_sub(q(1.0)), Q4_8::MIN);
+    assert_eq!(UQ2_2::MIN.saturating_sub(UQ2_2::DELTA), UQ2_2::MIN);
+
+    let mut value = q(1.0);
+    value += q(0.5);
+    value *= q(2.0);
+    value -= q(1.0);
+    value /= q(4.0);
+    assert_eq!(value, q(0.5));
+}
+
+#[test]
+fn wide_arithmetic() {
+    type UQ1_100 = UFixed<1, 100>;
+    let one = UQ1_100::from_bits(1 << 100).unwrap();
+    let half = UQ1_100::from_bits(1 << 99).unwrap();
+    assert_eq!(one * one, one);
+    assert_eq!(one / one, one);
+    assert_eq!(half * half, UQ1_100::from_bits(1 << 98).unwrap());
+    assert_eq!(half / one, half);
+    assert_eq!(UQ1_100::DELTA * half, UQ1_100::MIN);
+    assert_eq!(UQ1_100::DELTA / half, UQ1_100::from_bits(2).unwrap());
+    assert_eq!(UQ1_100::MAX.checked_mul(UQ1_100::MAX), None);
+    assert_eq!(one.checked_div(half), None);
+    assert_eq!(one.checked_div(UQ1_100::MIN), None);
+
+    type Q2_100 = Fixed<2, 100>;
+    let q = |value| Q2_100::from_f64(value, Rounding::Nearest).unwrap();
+    assert_eq!(q(1.5) * q(-0.5), q(-0.75));
+    assert_eq!(q(-1.0) * q(-1.0), q(1.0));
+    assert_eq!(q(-1.0) / q(0.5), Q2_100::MIN);
+    assert_eq!(q(0.75) / q(-1.5), q(-0.5));
+    assert_eq!((-Q2_100::DELTA) * q(0.5), q(0.0));
+    assert_eq!(Q2_100::MIN.checked_mul(q(-1.0)), None);
+    assert_eq!(Q2_100::MIN.checked_div(q(-1.0)), None);
+
+    type UQ0_127 = UFixed<0, 127>;
+    let half = UQ0_127::from_bits(1 << 126).unwrap();
+    assert_eq!(half * half, UQ0_127::from_bits(1 << 125).unwrap());
+    assert_eq!(UQ0_127::DELTA / half, UQ0_127::from_bits(2).unwrap());
+    assert_eq!(half.checked_div(half), None);
+}
+
+#[test]
+#[should_panic = "attempt to add with overflow"]
+fn overflow() {
+    let _ = UQ2_2::MAX + UQ2_2::DELTA;
+}
+
+#[test]
+fn formatting() {
+    let value = Q4_8::from_f64(-2.25, Rounding::Nearest).unwrap();
+    assert_eq!(format!("{value}"), "-2.25");
+    assert_eq!(format!("{value:?}"), "-2.25");
+}
+
+bitfields! {
+    struct Gains : u32 {
+        gain: Q4_8,
+        ratio: UQ2_2,
+    }
+}
+
+#[test]
+fn bitfields() {
+    let gain = Q4_8::from_f64(-2.25, Rounding::Nearest).unwrap();
+    let ratio = UQ2_2::from_f64(1.75, Rounding::Nearest).unwrap();
+    let mut gains = Gains::new(gain, ratio);
+    assert_eq!(gains.0.raw(), 0b0111 << 12 | 0xDC0);
+    assert_eq!(gains.gain(), gain);
+    assert_eq!(gains.ratio(), ratio);
+    gains.set_gain(Q4_8::MAX);
+    assert_eq!(gains.gain(), Q4_8::MAX);
+    assert_eq!(gains.ratio(), ratio);
+}
@@ -0,0 +1,91 @@
+use superbitty::{bitfields, i1, i127, i4, BitFieldCompatible, Int};
+
+#[test]
+fn constants() {
+    assert_eq!(i1::MIN.value(), -1);
+    assert_eq!(i1::MAX.value(), 0);
+    assert_eq!(i4::MIN.value(), -8);
+    assert_eq!(i4::MAX.value(), 7);
+    assert_eq!(i127::MIN.value(), i128::MIN >> 1);
+    assert_eq!(i127::MAX.value(), i128::MAX >> 1);
+    assert_eq!(<i4 as BitFieldCompatible>::BITS_LEN, 4);
+    assert_eq!(<i4 as BitFieldCompatible>::SHIFT, 0);
+}
+
+#[test]
+fn constructors() {
+    assert_eq!(i4::new(-8).map(i4::value), Some(-8));
+    assert_eq!(i4::new(8), None);
+    assert_eq!(i4::new(-9), None);
+    assert_eq!(i4::new_wrapping(0b1111).value(), -1);
+    assert_eq!(i4::new_wrapping(9).value(), -7);
+    assert_eq!(i4::new_saturating(100), i4::MAX);
+    assert_eq!(i4::new_saturating(-100), i4::MIN);
+}
+
+#[test]
+fn arithmetic() {
+    let three = i4::new(3).unwrap();
+    let minus_two = i4::new(-2).unwrap();
+    assert_eq!((three + minus_two).value(), 1);
+    assert_eq!((minus_two - three).value(), -5);
+    assert_eq!((three * minus_two).value(), -6);
+    assert_eq!((three / minus_two).value(), -1);
+    assert_eq!((three % minus_two).value(), 1);
+    assert_eq!((-three).value(), -3);
+    assert_eq!((!three).value(), -4);
+
+    assert_eq!(i4::MIN.checked_neg(), None);
+    assert_eq!(i4::MIN.checked_div(i4::new(-1).unwrap()), None);
+    assert_eq!(i4::MAX.checked_add(three), None);
+    assert_eq!(i4::MAX.wrapping_add(three).value(), -6);
+    assert_eq!(i4::MIN.saturating_sub(three), i4::MIN);
+    assert_eq!(i4::MIN.wrapping_neg(), i4::MIN);
+}
+
+#[test]
+fn raw_representation() {
+    assert_eq!(i4::new(-1).unwrap().into_raw(), 0b1111);
+    assert_eq!(i4::new(-8).unwrap().into_raw(), 0b1000);
+    assert_eq!(i4::new(7).unwrap().into_raw(), 0b0111);
+    // SAFETY: All 4-bit values are valid.
+    unsafe {
+        assert_eq!(i4::from_raw(0b1111).value(), -1);
+        assert_eq!(i4::from_raw(0b1000).value(), -8);
+        assert_eq!(i4::from_raw(0b0111).value(), 7);
+    }
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(i4::try_from(-8i8).map(i4::value), Ok(-8));
+    assert!(i4::try_from(8i64).is_err());
+    assert_eq!(i128::from(i4::MIN), -8);
+    assert_eq!(i8::try_from(Int::<12>::new(-128).unwrap()), Ok(-128));
+    assert!(i8::try_from(Int::<12>::new(-129).unwrap()).is_err());
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Deltas : u16 {
+        x: i4,
+        y: i4,
+        z: Int<8>,
+    }
+}
+
+#[test]
+fn sign_extension() {
+    let mut deltas = Deltas::new(i4::new(-8).unwrap(), i4::new(7).unwrap(), Int::new(-1).unwrap());
+    assert_eq!(deltas.0.raw(), 0b1111_1111_0111_1000);
+    assert_eq!(deltas.x().value(), -8);
+    assert_eq!(deltas.y().value(), 7);
+    assert_eq!(deltas.z().value(), -1);
+
+    deltas.set_y(i4::new(-3).unwrap());
+    deltas.set_z(Int::new(100).unwrap());
+    assert_eq!(deltas.x().value(), -8);
+    assert_eq!(deltas.y().value(), -3);
+    assert_eq!(deltas.z().value(), 100);
+    assert_eq!(format!("{deltas:?}"), "Deltas { x: -8, y: -3, z: 100 }");
+}
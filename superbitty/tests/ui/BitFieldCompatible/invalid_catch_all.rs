@@ -0,0 +1,31 @@
+use superbitty::BitFieldCompatible;
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[repr(u8)]
+enum TwoCatchAlls {
+    #[bit_field(catch_all)]
+    A(u8),
+    #[bit_field(catch_all)]
+    B(u8),
+}
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+enum UnitCatchAll {
+    A,
+    #[bit_field(catch_all)]
+    B,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[repr(u8)]
+enum OtherPayload {
+    A(u8),
+    #[bit_field(catch_all)]
+    B(u8),
+}
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(catch_all)]
+struct OnStruct(u8);
+
+fn main() {}
@@ -0,0 +1,22 @@
+use superbitty::BitFieldCompatible;
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(compact)]
+struct OnStruct(superbitty::u3);
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+enum OnVariant {
+    #[bit_field(compact)]
+    A,
+}
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(compact)]
+#[repr(u8)]
+enum WithCatchAll {
+    A,
+    #[bit_field(catch_all)]
+    B(u8),
+}
+
+fn main() {}
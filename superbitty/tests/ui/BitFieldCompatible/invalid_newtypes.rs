@@ -0,0 +1,18 @@
+use superbitty::BitFieldCompatible;
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+struct NoWidth(u8);
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(bits = 3)]
+struct TwoFields(u8, u8);
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(bits = "3")]
+struct StringWidth(u8);
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(width = 3)]
+struct UnknownOption(u8);
+
+fn main() {}
@@ -0,0 +1,17 @@
+use superbitty::{bitfields, BitFieldCompatible};
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[repr(u8)]
+enum TooNarrow {
+    A = 0x10,
+    #[bit_field(catch_all)]
+    B(superbitty::u4),
+}
+
+bitfields! {
+    struct Bitfields : u8 {
+        too_narrow: TooNarrow,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use superbitty::{bitfields, BitFieldCompatible};
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(bits = 2)]
+enum TooNarrow {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+bitfields! {
+    struct Bitfields : u8 {
+        too_narrow: TooNarrow,
+    }
+}
+
+fn main() {}
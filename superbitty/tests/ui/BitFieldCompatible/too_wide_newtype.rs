@@ -0,0 +1,13 @@
+use superbitty::{bitfields, BitFieldCompatible};
+
+#[derive(BitFieldCompatible, Clone, Copy)]
+#[bit_field(bits = 9)]
+struct TooWide(u8);
+
+bitfields! {
+    struct Bitfields : u16 {
+        too_wide: TooWide,
+    }
+}
+
+fn main() {}
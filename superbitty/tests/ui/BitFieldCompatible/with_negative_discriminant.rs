@@ -1,8 +0,0 @@
-use superbitty::BitFieldCompatible;
-
-#[derive(BitFieldCompatible)]
-enum WithNegativeDiscriminant {
-    A = -1,
-}
-
-fn main() {}
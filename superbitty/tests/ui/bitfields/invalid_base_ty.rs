@@ -8,4 +8,8 @@ bitfields! {
     struct OtherBaseTy : Option<String> {}
 }
 
+bitfields! {
+    struct WrongWord : [u16; 4] {}
+}
+
 fn main() {}
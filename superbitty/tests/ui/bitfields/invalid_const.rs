@@ -0,0 +1,30 @@
+use superbitty::{bitfields, BitFieldCompatible};
+
+bitfields! {
+    #[bitfields(const, order = msb0, const)]
+    struct TwoConsts : u8 {}
+}
+
+#[derive(Clone, Copy)]
+struct Manual;
+
+// SAFETY: `into_raw()` always returns zero.
+unsafe impl BitFieldCompatible for Manual {
+    const SHIFT: u32 = 0;
+    const BITS_LEN: u32 = 0;
+    fn into_raw(self) -> u128 {
+        0
+    }
+    unsafe fn from_raw(_v: u128) -> Self {
+        Self
+    }
+}
+
+bitfields! {
+    #[bitfields(const)]
+    struct ManualField : u8 {
+        manual: Manual,
+    }
+}
+
+fn main() {}
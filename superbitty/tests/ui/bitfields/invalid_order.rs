@@ -0,0 +1,14 @@
+use superbitty::bitfields;
+
+bitfields! {
+    #[bitfields(order = big_endian)]
+    struct UnknownOrder : u8 {}
+}
+
+bitfields! {
+    #[bitfields(order = msb0)]
+    #[bitfields(order = lsb0)]
+    struct TwoOrders : u8 {}
+}
+
+fn main() {}
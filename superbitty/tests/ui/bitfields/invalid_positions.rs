@@ -0,0 +1,55 @@
+use superbitty::{bitfields, u2, u4};
+
+bitfields! {
+    struct Overlapping : u8 {
+        #[bits(0..4)]
+        a: u4,
+        #[bits(3..5)]
+        b: u2,
+    }
+}
+
+bitfields! {
+    struct TooNarrow : u8 {
+        #[bits(0..3)]
+        a: u4,
+    }
+}
+
+bitfields! {
+    struct TooWide : u8 {
+        #[bits(0..4)]
+        a: u2,
+    }
+}
+
+bitfields! {
+    struct Empty : u8 {
+        #[bits(4..4)]
+        a: u2,
+    }
+}
+
+bitfields! {
+    struct Outside : u8 {
+        #[bits(6..10)]
+        a: u4,
+    }
+}
+
+bitfields! {
+    struct TwoPositions : u8 {
+        #[bit(0)]
+        #[bits(0..1)]
+        a: bool,
+    }
+}
+
+bitfields! {
+    struct OpenRange : u8 {
+        #[bits(4..)]
+        a: u4,
+    }
+}
+
+fn main() {}
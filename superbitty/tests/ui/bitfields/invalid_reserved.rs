@@ -0,0 +1,33 @@
+use superbitty::{bitfields, u4};
+
+bitfields! {
+    struct ReservedField : u8 {
+        #[reserved]
+        a: u4,
+    }
+}
+
+bitfields! {
+    struct UnknownMode : u8 {
+        #[reserved(ignore)]
+        _: 4,
+    }
+}
+
+bitfields! {
+    struct OtherAttribute : u8 {
+        #[allow(dead_code)]
+        _: 4,
+    }
+}
+
+bitfields! {
+    struct Overlapping : u8 {
+        #[bits(0..4)]
+        a: u4,
+        #[bits(2..6)]
+        _: 4,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,53 @@
+use superbitty::{bitfields, u4};
+
+bitfields! {
+    struct TwoPrimaries : u8 {
+        union {
+            #[primary]
+            a { a: u4 },
+            #[primary]
+            b { b: u4 },
+        },
+    }
+}
+
+bitfields! {
+    struct Nested : u8 {
+        union {
+            a { a: u4 },
+            b {
+                union {
+                    c { c: u4 },
+                },
+            },
+        },
+    }
+}
+
+bitfields! {
+    struct PositionInView : u8 {
+        union {
+            a {
+                #[bits(0..4)]
+                a: u4,
+            },
+        },
+    }
+}
+
+bitfields! {
+    struct ArrayInAlternative : u8 {
+        union {
+            a { a: u8 },
+            b { b: [u4; 2] },
+        },
+    }
+}
+
+bitfields! {
+    struct Empty : u8 {
+        union {},
+    }
+}
+
+fn main() {}
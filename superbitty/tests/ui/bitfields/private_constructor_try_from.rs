@@ -0,0 +1,22 @@
+use superbitty::bitfields;
+
+mod inner {
+    superbitty::bitfields! {
+        pub struct Restricted : u8 {
+            pub flag: bool,
+        }
+        construct = fn new();
+    }
+}
+
+bitfields! {
+    struct Private : u8 {
+        flag: bool,
+    }
+    construct = pub(crate) fn new();
+}
+
+fn main() {
+    _ = inner::Restricted::try_from(0u8);
+    _ = Private::try_from(0u8);
+}
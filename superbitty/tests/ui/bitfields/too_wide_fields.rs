@@ -0,0 +1,15 @@
+use superbitty::bitfields;
+
+bitfields! {
+    struct NoRoomForPresenceBit : [u64; 4] {
+        a: Option<(u64, u64)>,
+    }
+}
+
+bitfields! {
+    struct TooWideArray : [u64; 4] {
+        a: [u64; 3],
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use superbitty::bitfields;
+
+bitfields! {
+    #[derive(Clone, Copy)]
+    struct Wide : [u64; 3] {
+        a: u64,
+        b: u64,
+        c: u64,
+    }
+}
+
+bitfields! {
+    struct Nests : [u64; 4] {
+        wide: Wide,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,96 @@
+use superbitty::{bitfields, u1, u127, u3, u7, BitFieldCompatible, UInt};
+
+#[test]
+fn constants() {
+    assert_eq!(u1::BITS, 1);
+    assert_eq!(u1::MIN.value(), 0);
+    assert_eq!(u1::MAX.value(), 1);
+    assert_eq!(u7::MAX.value(), 0x7F);
+    assert_eq!(UInt::<12>::MAX.value(), 0xFFF);
+    assert_eq!(u127::MAX.value(), u128::MAX >> 1);
+    assert_eq!(<u7 as BitFieldCompatible>::BITS_LEN, 7);
+    assert_eq!(<u7 as BitFieldCompatible>::SHIFT, 0);
+}
+
+#[test]
+fn constructors() {
+    assert_eq!(u3::new(7).map(u3::value), Some(7));
+    assert_eq!(u3::new(8), None);
+    assert_eq!(u3::new_wrapping(0b1101).value(), 0b101);
+    assert_eq!(u3::new_saturating(100), u3::MAX);
+    assert_eq!(u3::new_saturating(2).value(), 2);
+}
+
+#[test]
+fn arithmetic() {
+    let two = u3::new(2).unwrap();
+    let five = u3::new(5).unwrap();
+    assert_eq!((two + five).value(), 7);
+    assert_eq!((five - two).value(), 3);
+    assert_eq!((two * two).value(), 4);
+    assert_eq!((five / two).value(), 2);
+    assert_eq!((five % two).value(), 1);
+    assert_eq!((five & two).value(), 0);
+    assert_eq!((five | two).value(), 7);
+    assert_eq!((five ^ u3::MAX).value(), 2);
+    assert_eq!((!five).value(), 2);
+
+    assert_eq!(five.checked_add(five), None);
+    assert_eq!(two.checked_sub(five), None);
+    assert_eq!(five.checked_mul(two), None);
+    assert_eq!(five.checked_div(u3::MIN), None);
+    assert_eq!(five.wrapping_add(five).value(), 2);
+    assert_eq!(two.wrapping_sub(five).value(), 5);
+    assert_eq!(five.wrapping_mul(two).value(), 2);
+    assert_eq!(five.saturating_add(five), u3::MAX);
+    assert_eq!(two.saturating_sub(five), u3::MIN);
+    assert_eq!(five.saturating_mul(five), u3::MAX);
+
+    let mut value = two;
+    value += two;
+    value -= u3::new(1).unwrap();
+    value *= two;
+    assert_eq!(value.value(), 6);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "attempt to add with overflow"]
+fn overflow_panics() {
+    let _ = u3::MAX + u3::new(1).unwrap();
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(u7::try_from(127u8).map(u7::value), Ok(127));
+    assert!(u7::try_from(128u8).is_err());
+    assert!(u7::try_from(1000u64).is_err());
+    assert_eq!(u128::from(u7::MAX), 127);
+    assert_eq!(u8::try_from(UInt::<12>::new(255).unwrap()), Ok(255));
+    assert!(u8::try_from(UInt::<12>::new(256).unwrap()).is_err());
+    assert_eq!(format!("{:?} {} {:x}", u7::MAX, u7::MAX, u7::MAX), "127 127 7f");
+}
+
+bitfields! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Counters : u16 {
+        flag: bool,
+        small: u3,
+        big: UInt<12>,
+    }
+}
+
+#[test]
+fn as_bitfields() {
+    let mut counters = Counters::new(true, u3::new(5).unwrap(), UInt::<12>::MAX);
+    assert_eq!(counters.0.raw(), 0b1111_1111_1111_1011);
+    assert_eq!(counters.small().value(), 5);
+    assert_eq!(counters.big(), UInt::<12>::MAX);
+
+    counters.set_small(u3::MIN);
+    counters.set_big(UInt::new(0xABC).unwrap());
+    assert!(counters.flag());
+    assert_eq!(counters.small(), u3::MIN);
+    assert_eq!(counters.big().value(), 0xABC);
+    assert_eq!(format!("{counters:?}"), "Counters { flag: true, small: 0, big: 2748 }");
+}